@@ -0,0 +1,146 @@
+use crate::{read_json_from_file, Extension, PantherError};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+/// A discrepancy between a published index and the repository it is built
+/// from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditFinding {
+    /// The published index lists an APK the repository does not have.
+    MissingApk { pkg: String, apk: String },
+    /// The repository has an APK no published extension refers to.
+    OrphanedApk { apk: String },
+    /// The repository has no icon for a published extension.
+    MissingIcon { pkg: String },
+    /// A published extension is not in the repository's own index.
+    NotInRepo { pkg: String },
+    /// An extension of the repository's own index is not published.
+    NotPublished { pkg: String },
+    /// The published version of an extension differs from the repository's.
+    VersionMismatch {
+        pkg: String,
+        published: String,
+        repo: String,
+    },
+}
+
+/// Cross-checks a published index against a checkout of the repository it is
+/// built from, i.e. the directory holding `index.min.json`, `apk/` and
+/// `icon/`.
+///
+/// This catches a broken publishing pipeline, which checking source URLs
+/// cannot see: entries pointing at APKs that were never uploaded, APKs left
+/// behind by removed extensions, missing icons, and a published index out of
+/// sync with the repository's own.
+///
+/// # Arguments
+///
+/// * `published`: The extensions of the published index.
+/// * `checkout`: The root of the repository checkout.
+///
+/// # Returns
+///
+/// * `Result<Vec<AuditFinding>, PantherError>`: Every discrepancy found,
+///   grouped by kind. An empty list means the index and the repository agree.
+///
+/// # Errors
+///
+/// * If `apk/` cannot be listed or the repository's index cannot be read.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use panther::{audit_repo, fetch_json_github, DEFAULT_INDEX_URL};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), panther::PantherError> {
+///     let published = fetch_json_github(DEFAULT_INDEX_URL).await?;
+///     for finding in audit_repo(&published, "extensions")? {
+///         println!("{:?}", finding);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn audit_repo<P: AsRef<Path>>(
+    published: &[Extension],
+    checkout: P,
+) -> Result<Vec<AuditFinding>, PantherError> {
+    let checkout = checkout.as_ref();
+    let apks = list_files(&checkout.join("apk"))?;
+    // Icons are optional in forks, an absent directory only means none.
+    let icons = list_files(&checkout.join("icon")).unwrap_or_default();
+    let repo: HashMap<String, Extension> = read_json_from_file(checkout.join("index.min.json"))?
+        .into_iter()
+        .map(|extension| (extension.pkg.clone(), extension))
+        .collect();
+
+    let mut findings = Vec::new();
+    let referenced: HashSet<&str> = published.iter().map(|e| e.apk.as_str()).collect();
+    for extension in published {
+        if !apks.contains(&extension.apk) {
+            findings.push(AuditFinding::MissingApk {
+                pkg: extension.pkg.clone(),
+                apk: extension.apk.clone(),
+            });
+        }
+    }
+    let mut orphans: Vec<&String> = apks
+        .iter()
+        .filter(|apk| !referenced.contains(apk.as_str()))
+        .collect();
+    orphans.sort();
+    findings.extend(
+        orphans
+            .into_iter()
+            .map(|apk| AuditFinding::OrphanedApk { apk: apk.clone() }),
+    );
+    for extension in published {
+        if !icons.is_empty() && !icons.contains(&format!("{}.png", extension.pkg)) {
+            findings.push(AuditFinding::MissingIcon {
+                pkg: extension.pkg.clone(),
+            });
+        }
+    }
+    for extension in published {
+        match repo.get(&extension.pkg) {
+            None => findings.push(AuditFinding::NotInRepo {
+                pkg: extension.pkg.clone(),
+            }),
+            Some(own) if own.version != extension.version => {
+                findings.push(AuditFinding::VersionMismatch {
+                    pkg: extension.pkg.clone(),
+                    published: extension.version.clone(),
+                    repo: own.version.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    let published_pkgs: HashSet<&str> = published.iter().map(|e| e.pkg.as_str()).collect();
+    let mut unpublished: Vec<&String> = repo
+        .keys()
+        .filter(|pkg| !published_pkgs.contains(pkg.as_str()))
+        .collect();
+    unpublished.sort();
+    findings.extend(
+        unpublished
+            .into_iter()
+            .map(|pkg| AuditFinding::NotPublished { pkg: pkg.clone() }),
+    );
+    Ok(findings)
+}
+
+/// Returns the names of the files in `dir`.
+fn list_files(dir: &Path) -> Result<HashSet<String>, PantherError> {
+    let mut names = HashSet::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            names.insert(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    Ok(names)
+}
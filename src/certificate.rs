@@ -0,0 +1,77 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The certificate a server presented over TLS, as captured by a check.
+///
+/// # Fields
+///
+/// * `subject`: Who the certificate was issued to (e.g. `CN=example.com`).
+/// * `issuer`: Who issued it, the certificate authority.
+/// * `not_after`: When it expires.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::CertificateInfo;
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let pem = b"-----BEGIN CERTIFICATE-----
+/// MIIBgTCCASegAwIBAgIUOv25i6PsYNWCqC/qen26FqY//60wCgYIKoZIzj0EAwIw
+/// FjEUMBIGA1UEAwwLZXhhbXBsZS5jb20wHhcNMjUwMTAxMDAwMDAwWhcNMzUwMTAx
+/// MDAwMDAwWjAWMRQwEgYDVQQDDAtleGFtcGxlLmNvbTBZMBMGByqGSM49AgEGCCqG
+/// SM49AwEHA0IABINuPICgweBvLszyrHaRdEuFrlbO+LCkm2/HTG7N2AzD1+gmmEke
+/// gbjPE6q11SBuUHyDVfqYqJWhabgAZ9x4Uh2jUzBRMB0GA1UdDgQWBBTb6d2iAPvB
+/// xuz2Gziu/B2opTJ+ljAfBgNVHSMEGDAWgBTb6d2iAPvBxuz2Gziu/B2opTJ+ljAP
+/// BgNVHRMBAf8EBTADAQH/MAoGCCqGSM49BAMCA0gAMEUCIANMLSbdMCHCqa45MPFg
+/// PaYMCV0wxsYESB8rkZDNECwJAiEAnUArmKms1sn8qAk/yTU5t0Tbu/Ec/IQoCHUK
+/// Pw4A5N0=
+/// -----END CERTIFICATE-----";
+/// let (_, pem) = x509_parser::pem::parse_x509_pem(pem).unwrap();
+///
+/// let certificate = CertificateInfo::from_der(&pem.contents).unwrap();
+/// assert_eq!(certificate.subject, "CN=example.com");
+/// // Expires on 2035-01-01.
+/// assert_eq!(certificate.not_after, UNIX_EPOCH + Duration::from_secs(2051222400));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    pub not_after: SystemTime,
+}
+
+impl CertificateInfo {
+    /// Reads a DER encoded certificate, returning `None` if it cannot be
+    /// parsed.
+    pub fn from_der(der: &[u8]) -> Option<Self> {
+        let (_, certificate) = x509_parser::parse_x509_certificate(der).ok()?;
+        let expiry = certificate.validity().not_after.timestamp();
+        let not_after = match u64::try_from(expiry) {
+            Ok(secs) => UNIX_EPOCH + Duration::from_secs(secs),
+            Err(_) => UNIX_EPOCH - Duration::from_secs(expiry.unsigned_abs()),
+        };
+        Some(CertificateInfo {
+            subject: certificate.subject().to_string(),
+            issuer: certificate.issuer().to_string(),
+            not_after,
+        })
+    }
+
+    /// Returns how many whole days are left before the certificate expires,
+    /// negative once it has.
+    pub fn days_left(&self) -> i64 {
+        const DAY: u64 = 24 * 60 * 60;
+        match self.not_after.duration_since(SystemTime::now()) {
+            Ok(left) => (left.as_secs() / DAY) as i64,
+            Err(e) => -((e.duration().as_secs() / DAY) as i64) - 1,
+        }
+    }
+
+    /// Returns whether the certificate expires within `window` from now, or
+    /// already has.
+    pub fn expires_within(&self, window: Duration) -> bool {
+        // A window past the end of time covers every certificate.
+        SystemTime::now()
+            .checked_add(window)
+            .is_none_or(|limit| self.not_after <= limit)
+    }
+}
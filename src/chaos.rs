@@ -0,0 +1,70 @@
+use crate::{check::failure, CheckResult, CheckStatus, PantherError};
+use rand::Rng;
+use reqwest::{Method, StatusCode};
+use std::{io, time::Duration};
+
+/// A failure chaos mode can stand in for a real check.
+#[derive(Debug, Clone, Copy)]
+enum Fault {
+    Timeout,
+    ServerError,
+    Dns,
+}
+
+const FAULTS: [Fault; 3] = [Fault::Timeout, Fault::ServerError, Fault::Dns];
+
+/// Fails the check of `url` on purpose with probability `rate`, returning
+/// the made up result, or `None` to check it for real. `method` is the one
+/// the request would have been sent with.
+///
+/// Faults get the status, HTTP status and timings of their real counterpart,
+/// so that alerting rules and dashboards cannot tell them apart. Their
+/// errors differ: a real timeout carries a [`PantherError::Timeout`], which
+/// only reqwest can build, while a fake one carries an I/O error of kind
+/// [`io::ErrorKind::TimedOut`].
+pub(crate) fn inject(url: &str, rate: f64, method: &Method) -> Option<CheckResult> {
+    let mut rng = rand::rng();
+    if rate <= 0.0 || !rng.random_bool(rate.min(1.0)) {
+        return None;
+    }
+    let result = match FAULTS[rng.random_range(0..FAULTS.len())] {
+        Fault::Timeout => {
+            let e = io::Error::new(io::ErrorKind::TimedOut, "timeout injected by chaos mode");
+            CheckResult {
+                method: Some(method.clone()),
+                ..failure(url, CheckStatus::Timeout, e.into())
+            }
+        }
+        Fault::ServerError => {
+            let ttfb = Duration::from_millis(rng.random_range(20..300));
+            CheckResult {
+                http_status: Some(StatusCode::SERVICE_UNAVAILABLE),
+                ttfb: Some(ttfb),
+                total: Some(ttfb + Duration::from_millis(rng.random_range(1..20))),
+                method: Some(method.clone()),
+                error: None,
+                ..failure(
+                    url,
+                    CheckStatus::NotAvailable,
+                    PantherError::Http(StatusCode::SERVICE_UNAVAILABLE),
+                )
+            }
+        }
+        Fault::Dns => {
+            let host = reqwest::Url::parse(url)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_string))
+                .unwrap_or_else(|| url.to_string());
+            let e = PantherError::Unresolved {
+                host,
+                reason: "failure injected by chaos mode".to_string(),
+            };
+            failure(url, CheckStatus::Error, e)
+        }
+    };
+    Some(CheckResult {
+        path: Some("/".to_string()),
+        attempts: 1,
+        ..result
+    })
+}
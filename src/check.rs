@@ -0,0 +1,1169 @@
+use crate::{
+    dns::Lookup, limit::HostLimiter, parse_retry_after, policy::for_domain,
+    timing::trace_timing_with, AcceptPolicy, CertificateInfo, DnsServer, Extension, HostLimit,
+    PantherError, RetryPolicy, Source, StatusSet, Timing,
+};
+use futures::{future::Either, Stream, StreamExt};
+use reqwest::{
+    dns::{Addrs, Name, Resolve, Resolving},
+    header::{
+        HeaderMap, HeaderName, AUTHORIZATION, COOKIE, LOCATION, PROXY_AUTHORIZATION, RETRY_AFTER,
+        WWW_AUTHENTICATE,
+    },
+    Client, Method, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt, io,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+use tokio_native_tls::native_tls;
+
+/// Paths tried, in order, when testing a source. Plenty of sources answer
+/// `404` on `/` and are still perfectly alive on their listing pages.
+pub const PROBE_PATHS: &[&str] = &["/", "/latest", "/search?q=a"];
+
+/// How a checked URL was classified.
+///
+/// # Variants
+///
+/// * `Available`: The URL answered with a status accepted by
+///   [`CheckOptions::accept`], any success status by default.
+/// * `NotAvailable`: The URL answered with a server error (`5xx`).
+/// * `Unexpected`: The URL answered with any other status (redirects that
+///   were not followed, `4xx`...).
+/// * `Timeout`: The request timed out, or the run deadline passed before the
+///   check could finish.
+/// * `Expiring`: The URL answered with an accepted status, but over TLS
+///   with a certificate expiring within
+///   [`CheckOptions::cert_expiry_warning`], or already expired. A warning:
+///   the source still works, for now.
+/// * `Reaped`: A request hung far past its timeout (e.g. a stuck TLS
+///   handshake or a server trickling bytes) and was aborted, see
+///   [`CheckOptions::reap_factor`].
+/// * `Error`: No response was received at all (DNS failure, refused
+///   connection, TLS error...).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Available,
+    Expiring,
+    NotAvailable,
+    Unexpected,
+    Timeout,
+    Reaped,
+    Error,
+}
+
+impl CheckStatus {
+    /// Returns whether the URL answered as expected, warnings aside.
+    pub fn is_available(self) -> bool {
+        matches!(self, CheckStatus::Available | CheckStatus::Expiring)
+    }
+}
+
+/// An IP version, to check URLs over a single address family.
+///
+/// Some sources are only broken over IPv6, which goes unnoticed when
+/// connections fall back to IPv4.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IpVersion {
+    #[serde(rename = "ipv4")]
+    V4,
+    #[serde(rename = "ipv6")]
+    V6,
+}
+
+impl IpVersion {
+    /// Returns whether `ip` belongs to this IP version.
+    pub fn matches(self, ip: IpAddr) -> bool {
+        match self {
+            IpVersion::V4 => ip.is_ipv4(),
+            IpVersion::V6 => ip.is_ipv6(),
+        }
+    }
+}
+
+impl fmt::Display for IpVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IpVersion::V4 => f.write_str("IPv4"),
+            IpVersion::V6 => f.write_str("IPv6"),
+        }
+    }
+}
+
+/// The outcome of checking a single URL with [`check_url`].
+///
+/// # Fields
+///
+/// * `url`: The URL that was checked, as given to [`check_url`].
+/// * `status`: How the URL was classified.
+/// * `http_status`: The HTTP status code of the reported probe, if any
+///   response was received.
+/// * `path`: The probe path that produced the reported response.
+/// * `ttfb`: Time until the status line and headers arrived.
+/// * `total`: Time until the body was fully read.
+/// * `attempts`: How many requests were sent for the reported probe,
+///   retries included.
+/// * `method`: The HTTP method of the reported probe, `GET` when a `HEAD`
+///   request was rejected and retried as `GET`.
+/// * `error`: The request error, when `status` is [`CheckStatus::Error`] or
+///   [`CheckStatus::Timeout`].
+/// * `capture`: The headers and start of the body of the reported response,
+///   kept for failed checks when [`CheckOptions::capture_bytes`] is set, and
+///   for every check with [`CheckOptions::keep_headers`].
+/// * `redirects`: The redirect chain followed by the reported probe, from
+///   the probed URL to the final one. Empty when there was no redirect.
+/// * `timing`: How long each step of a request to the reported probe's URL
+///   took, when [`CheckOptions::trace_timing`] is set and the trace worked.
+/// * `timing_error`: Why the request was not timed although
+///   [`CheckOptions::trace_timing`] is set: the trace failed, or it was
+///   skipped because requests go through [`CheckOptions::proxy`].
+/// * `insecure`: Whether the response came over TLS without its certificate
+///   being verified, see [`CheckOptions::insecure`].
+/// * `certificate`: The certificate presented by the server of the reported
+///   response, for `https` URLs.
+/// * `ip_version`: The only IP version the URL was checked over, see
+///   [`CheckOptions::ip_version`].
+/// * `resolved`: The addresses the host of the reported response resolved
+///   to, empty for IP addresses or when resolution failed.
+/// * `throttled`: Whether the server answered `429 Too Many Requests` to the
+///   reported probe. Such an answer is retried once, on top of the retries of
+///   [`CheckOptions::retry`], after the delay its `Retry-After` header asks
+///   for but no longer than [`RetryPolicy::max_delay`], so a rate limited but
+///   healthy source still reports its real status.
+#[derive(Debug)]
+pub struct CheckResult {
+    pub url: String,
+    pub status: CheckStatus,
+    pub http_status: Option<StatusCode>,
+    pub path: Option<String>,
+    pub ttfb: Option<Duration>,
+    pub total: Option<Duration>,
+    pub attempts: u32,
+    pub method: Option<Method>,
+    pub error: Option<PantherError>,
+    pub capture: Option<Capture>,
+    pub redirects: Vec<String>,
+    pub timing: Option<Timing>,
+    pub timing_error: Option<String>,
+    pub insecure: bool,
+    pub certificate: Option<CertificateInfo>,
+    pub ip_version: Option<IpVersion>,
+    pub resolved: Vec<IpAddr>,
+    pub throttled: bool,
+}
+
+impl CheckResult {
+    /// Returns the URL that produced the reported response, if the probe was
+    /// redirected.
+    pub fn final_url(&self) -> Option<&str> {
+        self.redirects.last().map(String::as_str)
+    }
+
+    /// Returns the host redirects ended up on, if it differs from the host of
+    /// [`CheckResult::url`]. That usually means the source moved to another
+    /// domain and its base URL should be updated in the index.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use panther::{CheckResult, CheckStatus};
+    ///
+    /// let result = CheckResult {
+    ///     url: "https://example.com".to_string(),
+    ///     status: CheckStatus::Available,
+    ///     http_status: None,
+    ///     path: None,
+    ///     ttfb: None,
+    ///     total: None,
+    ///     attempts: 1,
+    ///     method: None,
+    ///     error: None,
+    ///     capture: None,
+    ///     redirects: vec![
+    ///         "https://example.com/".to_string(),
+    ///         "https://www.example.org/".to_string(),
+    ///     ],
+    ///     timing: None,
+    ///     timing_error: None,
+    ///     insecure: false,
+    ///     certificate: None,
+    ///     ip_version: None,
+    ///     resolved: Vec::new(),
+    ///     throttled: false,
+    /// };
+    /// assert_eq!(result.moved_to().as_deref(), Some("www.example.org"));
+    /// ```
+    pub fn moved_to(&self) -> Option<String> {
+        let host = |url: &str| {
+            reqwest::Url::parse(url)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+        };
+        let to = host(self.final_url()?)?;
+        (host(&self.url).as_ref() != Some(&to)).then_some(to)
+    }
+}
+
+/// The headers and first bytes of the body of a response, kept for
+/// analyzing failures after the fact.
+///
+/// # Fields
+///
+/// * `headers`: The response headers.
+/// * `body`: At most [`CheckOptions::capture_bytes`] bytes from the start of
+///   the body.
+#[derive(Debug, Clone)]
+pub struct Capture {
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+/// A URL to check, along with where it was found in an index.
+///
+/// # Fields
+///
+/// * `url`: The URL to check.
+/// * `extension`: The name of the extension providing the source.
+/// * `source`: The name of the source.
+/// * `source_id`: The unique identifier of the source.
+/// * `lang`: The language of the source.
+/// * `repo`: The repository whose index lists the source, when several may
+///   be checked together.
+///
+/// URLs checked on their own, outside of any index, only have `url` set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Target {
+    pub url: String,
+    pub extension: Option<String>,
+    pub source: Option<String>,
+    pub source_id: Option<String>,
+    pub lang: Option<String>,
+    pub repo: Option<String>,
+}
+
+impl Target {
+    /// Builds the target checking `source`'s base URL.
+    pub fn from_source(extension: &Extension, source: &Source) -> Self {
+        Target {
+            url: source.base_url.clone(),
+            extension: Some(extension.name.clone()),
+            source: Some(source.name.clone()),
+            source_id: Some(source.id.clone()),
+            lang: Some(source.lang.clone()),
+            repo: None,
+        }
+    }
+}
+
+impl From<String> for Target {
+    fn from(url: String) -> Self {
+        Target {
+            url,
+            ..Target::default()
+        }
+    }
+}
+
+/// Settings shared by every check of a run.
+///
+/// # Fields
+///
+/// * `paths`: The paths to probe on each URL, in order. An empty list probes
+///   the URL itself.
+/// * `retry`: How transient failures are retried.
+/// * `timeout`: How long a single request may take, body included.
+/// * `deadline`: How long [`Checker::check_all`] may run. Checks still
+///   running or not started yet when it passes are reported as
+///   [`CheckStatus::Timeout`].
+/// * `concurrency`: The maximum number of checks running at once in
+///   [`Checker::check_all`]. `0` is treated as `1`.
+/// * `host_limit`: How many requests to the same host may run at once, and
+///   how far apart they start. Not limited by default.
+/// * `capture_bytes`: How many bytes of the body of failed responses to keep
+///   in [`CheckResult::capture`]. `0`, the default, keeps nothing.
+/// * `keep_headers`: Whether to keep the headers of every reported response
+///   in [`CheckResult::capture`], available ones included, for display.
+/// * `method`: The HTTP method of probes, `GET` by default. With `HEAD`,
+///   bodies are not downloaded, and servers answering `405 Method Not
+///   Allowed` or `501 Not Implemented` are probed again with `GET`.
+/// * `accept`: Which status codes count as available.
+/// * `prefetch_dns`: Whether [`Checker::check_all`] resolves hosts in a stage
+///   of its own, ahead of the requests.
+/// * `reap_factor`: How many times `timeout` a single request may run before
+///   the watchdog aborts it and the check is reported as
+///   [`CheckStatus::Reaped`], in case a request does not honor its timeout.
+///   `0` disables the watchdog, as does having no `timeout`.
+/// * `trace_timing`: Whether to fill [`CheckResult::timing`], which costs an
+///   extra request per check, see [`crate::trace_timing`].
+/// * `pool_max_idle_per_host`: How many idle connections to each host are
+///   kept open for reuse.
+/// * `pool_idle_timeout`: How long an idle connection is kept open, forever
+///   with `None`.
+/// * `proxy`: The URL of an HTTP or SOCKS5 proxy (e.g. `socks5://host:1080`)
+///   every request goes through. Without one, the `HTTP_PROXY`,
+///   `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY` environment variables apply.
+/// * `headers`: Headers sent with every request, such as a `User-Agent`
+///   many sites require.
+/// * `domain_headers`: Headers for a domain, in lower case, and its
+///   subdomains, added to `headers` and replacing those of the same name.
+///   When several domains match, the most specific one wins.
+/// * `ca_certs`: PEM encoded certificates, one or more per entry, of
+///   certificate authorities trusted on top of the system ones, for sources
+///   with a private or self-signed chain.
+/// * `insecure`: Whether to accept any certificate, expired, self-signed or
+///   for another host. Responses received over TLS then have
+///   [`CheckResult::insecure`] set, since they prove the server answers but
+///   not that browsers would trust it.
+/// * `cert_expiry_warning`: How close to its expiry a certificate makes an
+///   available URL [`CheckStatus::Expiring`], 14 days by default.
+/// * `ip_version`: Connect over this IP version only, failing hosts without
+///   an address of that version. With `None`, any address may be used.
+/// * `dns`: Where host names are resolved, the system resolver by default.
+/// * `chaos`: The share of checks, from `0.0` to `1.0`, failed on purpose
+///   with a simulated timeout, `503` response or DNS failure instead of
+///   being run, to make sure alerting fires. Needs the `chaos` feature.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::{CheckOptions, RetryPolicy};
+///
+/// let options = CheckOptions {
+///     concurrency: 32,
+///     retry: RetryPolicy {
+///         max_attempts: 5,
+///         ..RetryPolicy::default()
+///     },
+///     ..CheckOptions::default()
+/// };
+/// assert_eq!(options.paths[0], "/");
+/// ```
+#[derive(Debug, Clone)]
+pub struct CheckOptions {
+    pub paths: Vec<String>,
+    pub retry: RetryPolicy,
+    pub timeout: Option<Duration>,
+    pub deadline: Option<Duration>,
+    pub concurrency: usize,
+    pub host_limit: HostLimit,
+    pub capture_bytes: usize,
+    pub keep_headers: bool,
+    pub method: Method,
+    pub accept: AcceptPolicy,
+    pub prefetch_dns: bool,
+    pub reap_factor: u32,
+    pub trace_timing: bool,
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout: Option<Duration>,
+    pub proxy: Option<String>,
+    pub headers: HeaderMap,
+    pub domain_headers: HashMap<String, HeaderMap>,
+    pub ca_certs: Vec<Vec<u8>>,
+    pub insecure: bool,
+    pub cert_expiry_warning: Duration,
+    pub ip_version: Option<IpVersion>,
+    pub dns: DnsServer,
+    #[cfg(feature = "chaos")]
+    pub chaos: f64,
+}
+
+impl Default for CheckOptions {
+    fn default() -> Self {
+        CheckOptions {
+            paths: PROBE_PATHS.iter().map(|path| path.to_string()).collect(),
+            retry: RetryPolicy::default(),
+            timeout: Some(Duration::from_secs(30)),
+            deadline: None,
+            concurrency: 16,
+            host_limit: HostLimit::default(),
+            capture_bytes: 0,
+            keep_headers: false,
+            method: Method::GET,
+            accept: AcceptPolicy::default(),
+            prefetch_dns: false,
+            reap_factor: 3,
+            trace_timing: false,
+            pool_max_idle_per_host: 32,
+            pool_idle_timeout: Some(Duration::from_secs(90)),
+            proxy: None,
+            headers: HeaderMap::new(),
+            domain_headers: HashMap::new(),
+            ca_certs: Vec::new(),
+            insecure: false,
+            cert_expiry_warning: Duration::from_secs(14 * 24 * 60 * 60),
+            ip_version: None,
+            dns: DnsServer::System,
+            #[cfg(feature = "chaos")]
+            chaos: 0.0,
+        }
+    }
+}
+
+/// Checks the availability of a given URL by sending HTTP GET requests.
+///
+/// Each path in `options.paths` is appended to `url` and probed in order
+/// until one of them answers with a success status. If none succeed, the
+/// response to the first probe is reported. A request error (DNS, TLS,
+/// refused connection...) stops probing right away, since every path lives on
+/// the same host.
+///
+/// Transient failures, i.e. retryable errors and `5xx` responses, are retried
+/// according to `options.retry` before a probe is given up on; the number of
+/// attempts made for the reported probe is part of the result.
+///
+/// Two timings are recorded: the time-to-first-byte, taken when the status
+/// line and headers arrive, and the total time once the body has been fully
+/// read. A small TTFB with a large total usually means a fast server serving
+/// a slow page (e.g. a challenge page), not a slow server.
+///
+/// This builds a [`Checker`] for a single check; to check several URLs,
+/// build one and reuse it so that connections are pooled.
+///
+/// # Arguments
+///
+/// * `url`: A string slice representing the URL to check.
+/// * `options`: The probe paths and retry policy to use.
+///
+/// # Returns
+///
+/// * `CheckResult`: The classification of the URL along with the details of
+///   the reported probe. Request failures are part of the result rather than
+///   an error, so a dead source never aborts a run.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use panther::{check_url, CheckOptions, CheckStatus};
+/// use tokio;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let result = check_url("https://www.google.com", &CheckOptions::default()).await;
+///     if result.status == CheckStatus::Available {
+///         println!("{} is up after {} attempt(s)", result.url, result.attempts);
+///     }
+/// }
+/// ```
+pub async fn check_url(url: &str, options: &CheckOptions) -> CheckResult {
+    match Checker::new(options.clone()) {
+        Ok(checker) => checker.check(url).await,
+        Err(e) => failure(url, CheckStatus::Error, e),
+    }
+}
+
+/// Checks URLs with the same settings and a single HTTP client, so that
+/// connections and TLS sessions are reused from one check to the next.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use panther::{CheckOptions, Checker};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), panther::PantherError> {
+///     let checker = Checker::new(CheckOptions::default())?;
+///     for url in ["https://example.com", "https://example.com/latest"] {
+///         println!("{}: {:?}", url, checker.check(url).await.status);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Checker {
+    client: Client,
+    options: CheckOptions,
+    lookups: Lookups,
+    dns: Lookup,
+    limiter: Arc<HostLimiter>,
+    tls: Option<native_tls::TlsConnector>,
+}
+
+impl Checker {
+    /// Builds a checker applying `options` to every check.
+    ///
+    /// # Errors
+    ///
+    /// * If the HTTP client cannot be built, e.g. when the TLS backend fails
+    ///   to initialize, the proxy URL is invalid or a CA certificate cannot
+    ///   be parsed.
+    pub fn new(options: CheckOptions) -> Result<Self, PantherError> {
+        let lookups = Lookups::default();
+        let dns = Lookup::new(&options.dns, options.proxy.as_deref())?;
+        // Redirects are followed by `probe`, which keeps the chain.
+        let mut builder = Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .pool_max_idle_per_host(options.pool_max_idle_per_host)
+            .pool_idle_timeout(options.pool_idle_timeout)
+            .danger_accept_invalid_certs(options.insecure)
+            .tls_info(true);
+        for pem in &options.ca_certs {
+            for cert in reqwest::Certificate::from_pem_bundle(pem)? {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        if let Some(proxy) = &options.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+        builder = builder.dns_resolver(Arc::new(Resolver {
+            lookups: lookups.clone(),
+            prefetched: options.prefetch_dns,
+            ip_version: options.ip_version,
+            dns: dns.clone(),
+        }));
+        // Traces open connections of their own, with the same trust settings.
+        let tls = match options.trace_timing {
+            true => Some(tls_connector(&options)?),
+            false => None,
+        };
+        Ok(Checker {
+            client: builder.build()?,
+            limiter: Arc::new(HostLimiter::new(options.host_limit.clone())),
+            options,
+            lookups,
+            dns,
+            tls,
+        })
+    }
+
+    /// Returns the settings applied to every check.
+    pub fn options(&self) -> &CheckOptions {
+        &self.options
+    }
+
+    /// Returns the HTTP client shared by every check. The headers of the
+    /// options are added to each request rather than by the client.
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Checks `url`, see [`check_url`].
+    pub async fn check(&self, url: &str) -> CheckResult {
+        #[cfg(feature = "chaos")]
+        if let Some(result) = crate::chaos::inject(url, self.options.chaos, &self.options.method) {
+            return CheckResult {
+                ip_version: self.options.ip_version,
+                ..result
+            };
+        }
+        if let Some(version) = self.options.ip_version {
+            // IP addresses in URLs never go through the resolver.
+            let ip = reqwest::Url::parse(url).ok().and_then(|url| {
+                let host = url
+                    .host_str()?
+                    .trim_start_matches('[')
+                    .trim_end_matches(']');
+                host.parse::<IpAddr>().ok()
+            });
+            if let Some(ip) = ip.filter(|ip| !version.matches(*ip)) {
+                let e = PantherError::Unresolved {
+                    host: ip.to_string(),
+                    reason: format!("not an {} address", version),
+                };
+                return CheckResult {
+                    ip_version: Some(version),
+                    ..failure(url, CheckStatus::Error, e)
+                };
+            }
+        }
+        let mut result = self.probe_paths(url).await;
+        let window = self.options.cert_expiry_warning;
+        if result.status == CheckStatus::Available
+            && result
+                .certificate
+                .as_ref()
+                .is_some_and(|certificate| certificate.expires_within(window))
+        {
+            result.status = CheckStatus::Expiring;
+        }
+        if let Some(tls) = &self.tls {
+            let path = result.path.as_deref().unwrap_or("/");
+            let url = join_path(url, path);
+            let options = &self.options;
+            if options.proxy.is_some() {
+                // A trace connects directly, and would time another route.
+                result.timing_error = Some("not traced through a proxy".to_string());
+            } else {
+                let mut headers = options.headers.clone();
+                if let Some(domain_headers) = for_domain(&options.domain_headers, &url) {
+                    headers.extend(domain_headers.clone());
+                }
+                let _permit = self.limiter.acquire(&url).await;
+                let trace = trace_timing_with(
+                    &url,
+                    options.timeout,
+                    tls,
+                    options.ip_version,
+                    &self.dns,
+                    &headers,
+                );
+                match trace.await {
+                    Ok(timing) => result.timing = Some(timing),
+                    Err(e) => result.timing_error = Some(e.to_string()),
+                }
+            }
+        }
+        result.insecure = self.options.insecure
+            && result.http_status.is_some()
+            && std::iter::once(url)
+                .chain(result.redirects.iter().map(String::as_str))
+                .any(|url| url.starts_with("https:"));
+        result.ip_version = self.options.ip_version;
+        let host = reqwest::Url::parse(result.final_url().unwrap_or(url))
+            .ok()
+            .and_then(|url| url.domain().map(str::to_string));
+        if let Some(host) = host {
+            if let Some(Ok(addrs)) = self.lookups.lock().expect("lookups lock").get(&host) {
+                result.resolved = addrs.clone();
+            }
+        }
+        result
+    }
+
+    /// Checks many targets concurrently, yielding results as they complete.
+    ///
+    /// At most `options.concurrency` checks are in flight at any time;
+    /// results come out in completion order, not in the order of `targets`,
+    /// each paired with the target it belongs to.
+    ///
+    /// With `options.prefetch_dns`, hosts are resolved in a stage of their
+    /// own that runs several lookups per check slot, each host only once.
+    /// Targets whose host does not resolve are reported right away with a
+    /// [`PantherError::Unresolved`] error, without taking up a check slot for
+    /// a request doomed to fail; the others connect to the addresses found.
+    ///
+    /// When `options.deadline` is set, it starts counting when this method
+    /// is called. Once it passes, every remaining target still gets a result,
+    /// with the [`CheckStatus::Timeout`] status and a
+    /// [`PantherError::Deadline`] error, so the number of results always
+    /// matches the number of targets.
+    ///
+    /// # Arguments
+    ///
+    /// * `targets`: The targets to check. Being a stream, they can be
+    ///   produced lazily (e.g. from [`crate::stream_json_from_file`]) so that
+    ///   a large index is never held in memory at once.
+    ///
+    /// # Returns
+    ///
+    /// * `impl Stream<Item = (Target, CheckResult)>`: A stream yielding one
+    ///   result per target.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use futures::{stream, StreamExt};
+    /// use panther::{CheckOptions, Checker, Target};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), panther::PantherError> {
+    ///     let urls = vec!["https://example.com".to_string(), "https://example.org".to_string()];
+    ///     let targets = stream::iter(urls.into_iter().map(Target::from));
+    ///     let checker = Checker::new(CheckOptions::default())?;
+    ///     let mut results = Box::pin(checker.check_all(targets));
+    ///     while let Some((target, result)) = results.next().await {
+    ///         println!("{}: {:?}", target.url, result.status);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn check_all<'a, S>(&'a self, targets: S) -> impl Stream<Item = (Target, CheckResult)> + 'a
+    where
+        S: Stream<Item = Target> + 'a,
+    {
+        let deadline = self
+            .options
+            .deadline
+            .and_then(|deadline| tokio::time::Instant::now().checked_add(deadline));
+        let concurrency = self.options.concurrency.max(1);
+        let check = move |(target, resolved): (Target, Result<(), PantherError>)| async move {
+            let mut result = match resolved {
+                Ok(()) => self.check_with_deadline(&target.url, deadline).await,
+                Err(e) => failure(&target.url, CheckStatus::Error, e),
+            };
+            result.ip_version = self.options.ip_version;
+            (target, result)
+        };
+        // Without prefetching, targets are not pulled ahead of the checks.
+        let checks = if self.options.prefetch_dns {
+            Either::Left(
+                targets
+                    .map(move |target| async move {
+                        let resolved = self.prefetch(&target.url).await;
+                        (target, resolved)
+                    })
+                    .buffer_unordered(concurrency * DNS_LOOKUPS_PER_CHECK)
+                    .map(check),
+            )
+        } else {
+            Either::Right(targets.map(move |target| check((target, Ok(())))))
+        };
+        checks.buffer_unordered(concurrency)
+    }
+
+    /// Probes the paths of the options on `url` in turn, see [`check_url`].
+    async fn probe_paths(&self, url: &str) -> CheckResult {
+        /*
+        FIXME:
+        Improved error handling, add more status codes
+         */
+        let root = ["/".to_string()];
+        let paths = if self.options.paths.is_empty() {
+            &root[..]
+        } else {
+            &self.options.paths[..]
+        };
+        let accepted = self.options.accept.for_url(url);
+        let mut first_failure = None;
+        for path in paths {
+            let (outcome, attempts, method) = self.probe_with_fallback(&join_path(url, path)).await;
+            let probe = match outcome {
+                Ok(probe) => probe,
+                Err(e) => {
+                    let status = match e {
+                        PantherError::Timeout(_) => CheckStatus::Timeout,
+                        PantherError::Reaped(_) => CheckStatus::Reaped,
+                        _ => CheckStatus::Error,
+                    };
+                    return CheckResult {
+                        path: Some(path.to_string()),
+                        attempts,
+                        method: Some(method),
+                        ..failure(url, status, e)
+                    };
+                }
+            };
+            let result = CheckResult {
+                url: url.to_string(),
+                status: classify(probe.status, accepted),
+                http_status: Some(probe.status),
+                path: Some(path.to_string()),
+                ttfb: Some(probe.ttfb),
+                total: Some(probe.total),
+                attempts,
+                method: Some(method),
+                error: None,
+                capture: None,
+                redirects: probe.redirects,
+                timing: None,
+                timing_error: None,
+                insecure: false,
+                certificate: probe.certificate,
+                ip_version: None,
+                resolved: Vec::new(),
+                throttled: probe.throttled,
+            };
+            if result.status == CheckStatus::Available {
+                return CheckResult {
+                    capture: probe.capture.filter(|_| self.options.keep_headers),
+                    ..result
+                };
+            }
+            let result = CheckResult {
+                capture: probe.capture,
+                ..result
+            };
+            first_failure.get_or_insert(result);
+        }
+        first_failure.expect("at least one path is always probed")
+    }
+
+    /// Resolves the host of `url` ahead of its check, reusing the result of
+    /// an earlier lookup of the same host. Nothing is resolved for an IP
+    /// address, or a URL too broken to have a host, left for the request to
+    /// report.
+    async fn prefetch(&self, url: &str) -> Result<(), PantherError> {
+        let Ok(parsed) = reqwest::Url::parse(url) else {
+            return Ok(());
+        };
+        let Some(host) = parsed.domain() else {
+            return Ok(());
+        };
+        let cached = self
+            .lookups
+            .lock()
+            .expect("lookups lock")
+            .get(host)
+            .cloned();
+        let addrs = match cached {
+            Some(addrs) => addrs,
+            None => {
+                let addrs = self.dns.lookup(host).await.map_err(|e| e.to_string());
+                self.lookups
+                    .lock()
+                    .expect("lookups lock")
+                    .insert(host.to_string(), addrs.clone());
+                addrs
+            }
+        };
+        let addrs = match (addrs, self.options.ip_version) {
+            (Ok(addrs), Some(version)) if !addrs.iter().any(|ip| version.matches(*ip)) => {
+                Err(format!("no {} address found", version))
+            }
+            (addrs, _) => addrs,
+        };
+        match addrs {
+            Ok(_) => Ok(()),
+            Err(reason) => Err(PantherError::Unresolved {
+                host: host.to_string(),
+                reason,
+            }),
+        }
+    }
+
+    /// Runs [`Checker::check`], giving up once `deadline` has passed.
+    async fn check_with_deadline(
+        &self,
+        url: &str,
+        deadline: Option<tokio::time::Instant>,
+    ) -> CheckResult {
+        let Some(deadline) = deadline else {
+            return self.check(url).await;
+        };
+        match tokio::time::timeout_at(deadline, self.check(url)).await {
+            Ok(result) => result,
+            Err(_) => failure(url, CheckStatus::Timeout, PantherError::Deadline),
+        }
+    }
+
+    /// Sends a single `method` request to `url` and reads the whole response,
+    /// keeping its headers and the first `capture_bytes` of its body if that
+    /// is not `0`. Redirects are followed, and the chain is kept; past
+    /// [`MAX_REDIRECTS`], the last redirect is the response. Like reqwest's
+    /// own redirect policy, [`SENSITIVE_HEADERS`] are not sent to another
+    /// origin than the one of `url`.
+    async fn probe(&self, url: &str, mut method: Method) -> Result<Probe, PantherError> {
+        let start = Instant::now();
+        let origin = reqwest::Url::parse(url).ok().map(|url| url.origin());
+        let mut target = url.to_string();
+        let mut redirects = Vec::new();
+        let mut response = loop {
+            let mut headers = self.options.headers.clone();
+            if reqwest::Url::parse(&target).ok().map(|url| url.origin()) != origin {
+                for name in &SENSITIVE_HEADERS {
+                    headers.remove(name);
+                }
+            }
+            let mut request = self
+                .client
+                .request(method.clone(), &target)
+                .headers(headers);
+            // Headers set for the domain of the target itself are meant for it.
+            if let Some(headers) = for_domain(&self.options.domain_headers, &target) {
+                request = request.headers(headers.clone());
+            }
+            if let Some(timeout) = self.options.timeout {
+                // The timeout covers the whole chain of redirects.
+                request = request.timeout(timeout.saturating_sub(start.elapsed()));
+            }
+            let response = request.send().await?;
+            let next = response
+                .status()
+                .is_redirection()
+                .then(|| response.headers().get(LOCATION))
+                .flatten()
+                .and_then(|location| location.to_str().ok())
+                .and_then(|location| response.url().join(location).ok());
+            match next {
+                Some(next) if redirects.len() <= MAX_REDIRECTS => {
+                    if redirects.is_empty() {
+                        redirects.push(response.url().to_string());
+                    }
+                    if response.status() == StatusCode::SEE_OTHER && method != Method::HEAD {
+                        method = Method::GET;
+                    }
+                    target = next.to_string();
+                    redirects.push(target.clone());
+                }
+                _ => break response,
+            }
+        };
+        // `send` resolves as soon as the headers are in.
+        let ttfb = start.elapsed();
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| parse_retry_after(value, SystemTime::now()));
+        let certificate = response
+            .extensions()
+            .get::<reqwest::tls::TlsInfo>()
+            .and_then(|info| info.peer_certificate())
+            .and_then(CertificateInfo::from_der);
+        let capture_bytes = self.options.capture_bytes;
+        let keep = capture_bytes > 0 || self.options.keep_headers;
+        let mut capture = keep.then(|| Capture {
+            headers: response.headers().clone(),
+            body: Vec::new(),
+        });
+        loop {
+            match response.chunk().await {
+                Ok(Some(chunk)) => {
+                    if let Some(capture) = &mut capture {
+                        let room = capture_bytes - capture.body.len();
+                        capture
+                            .body
+                            .extend_from_slice(&chunk[..chunk.len().min(room)]);
+                    }
+                }
+                Ok(None) => break,
+                // The timeout covers the body too, and a page that never ends is
+                // no better than a server that never answers.
+                Err(e) if e.is_timeout() => return Err(e.into()),
+                // The status is already known, so a broken body only cuts the total short.
+                Err(_) => break,
+            }
+        }
+        Ok(Probe {
+            status: response.status(),
+            ttfb,
+            total: start.elapsed(),
+            capture,
+            redirects,
+            certificate,
+            retry_after,
+            throttled: false,
+        })
+    }
+
+    /// Probes `url` with the method of the options, falling back to `GET` if
+    /// the server rejects `HEAD` requests. Returns the last outcome along with
+    /// the total number of attempts made and the method of the last one.
+    async fn probe_with_fallback(&self, url: &str) -> (Result<Probe, PantherError>, u32, Method) {
+        let method = &self.options.method;
+        let (outcome, attempts) = self.probe_with_retry(url, method).await;
+        let rejected = *method == Method::HEAD
+            && matches!(&outcome, Ok(probe) if is_head_rejection(probe.status));
+        if !rejected {
+            return (outcome, attempts, method.clone());
+        }
+        let (outcome, more) = self.probe_with_retry(url, &Method::GET).await;
+        (outcome, attempts + more, Method::GET)
+    }
+
+    /// Probes `url`, retrying transient failures as the retry policy allows.
+    /// Returns the last outcome along with the number of attempts made.
+    ///
+    /// A `429 Too Many Requests` is always retried once, on top of the
+    /// transient retries, after its `Retry-After` delay or the usual one
+    /// without it, but never waiting longer than the longest retry delay.
+    async fn probe_with_retry(
+        &self,
+        url: &str,
+        method: &Method,
+    ) -> (Result<Probe, PantherError>, u32) {
+        let options = &self.options;
+        let policy = &options.retry;
+        let reap_after = options
+            .timeout
+            .filter(|_| options.reap_factor > 0)
+            .and_then(|timeout| timeout.checked_mul(options.reap_factor));
+        let mut attempt = 1;
+        // The retry of a 429 does not count against the transient ones.
+        let mut max_attempts = policy.max_attempts;
+        let mut throttled = false;
+        loop {
+            // Taken outside the watchdog, and held while redirects are
+            // followed, so a redirect to the same host never waits for it.
+            let permit = self.limiter.acquire(url).await;
+            let probe = self.probe(url, method.clone());
+            let outcome = match reap_after {
+                Some(limit) => tokio::time::timeout(limit, probe)
+                    .await
+                    .unwrap_or(Err(PantherError::Reaped(limit))),
+                None => probe.await,
+            };
+            drop(permit);
+            if let Ok(probe) = &outcome {
+                if probe.status == StatusCode::TOO_MANY_REQUESTS
+                    && !options.accept.accepts(url, probe.status)
+                    && !throttled
+                {
+                    throttled = true;
+                    let wait = probe.retry_after.unwrap_or_else(|| policy.delay(attempt));
+                    tokio::time::sleep(wait.min(policy.max_delay)).await;
+                    attempt += 1;
+                    max_attempts += 1;
+                    continue;
+                }
+            }
+            let transient = match &outcome {
+                // No point retrying a HEAD the server does not implement.
+                Ok(probe) if *method == Method::HEAD && is_head_rejection(probe.status) => false,
+                // An accepted status is final, even a server error.
+                Ok(probe) => {
+                    probe.status.is_server_error() && !options.accept.accepts(url, probe.status)
+                }
+                Err(e) => e.is_retryable(),
+            };
+            if !transient || attempt >= max_attempts {
+                let outcome = outcome.map(|probe| Probe { throttled, ..probe });
+                return (outcome, attempt);
+            }
+            tokio::time::sleep(policy.delay(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
+/// How many DNS lookups may run per check slot when resolving up front.
+/// Lookups are cheap and quick compared to checks, so the lookup stage can
+/// stay well ahead.
+const DNS_LOOKUPS_PER_CHECK: usize = 4;
+/// Lookups already made, by host. Failures keep their message.
+type Lookups = Arc<Mutex<HashMap<String, Result<Vec<IpAddr>, String>>>>;
+/// Resolves hosts with `dns` for the client of a [`Checker`], keeping only
+/// addresses of `ip_version` if set. Every lookup is recorded in `lookups`,
+/// and when `prefetched`, hosts found there by [`Checker::prefetch`] are not
+/// resolved again.
+struct Resolver {
+    lookups: Lookups,
+    prefetched: bool,
+    ip_version: Option<IpVersion>,
+    dns: Lookup,
+}
+
+impl Resolve for Resolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        let cached = match self.prefetched {
+            true => self
+                .lookups
+                .lock()
+                .expect("lookups lock")
+                .get(&host)
+                .cloned(),
+            false => None,
+        };
+        let lookups = self.lookups.clone();
+        let ip_version = self.ip_version;
+        let dns = self.dns.clone();
+        Box::pin(async move {
+            let mut addrs = match cached {
+                Some(Ok(addrs)) => addrs,
+                _ => {
+                    let addrs = dns.lookup(&host).await?;
+                    let mut lookups = lookups.lock().expect("lookups lock");
+                    lookups.insert(host, Ok(addrs.clone()));
+                    addrs
+                }
+            };
+            if let Some(version) = ip_version {
+                addrs.retain(|ip| version.matches(*ip));
+                if addrs.is_empty() {
+                    let message = format!("no {} address found", version);
+                    return Err(io::Error::new(io::ErrorKind::NotFound, message).into());
+                }
+            }
+            let addrs = addrs.into_iter().map(|ip| SocketAddr::new(ip, 0));
+            Ok(Box::new(addrs) as Addrs)
+        })
+    }
+}
+/// Builds the result of a check of `url` that got no response.
+pub(crate) fn failure(url: &str, status: CheckStatus, error: PantherError) -> CheckResult {
+    CheckResult {
+        url: url.to_string(),
+        status,
+        http_status: None,
+        path: None,
+        ttfb: None,
+        total: None,
+        attempts: 0,
+        method: None,
+        error: Some(error),
+        capture: None,
+        redirects: Vec::new(),
+        timing: None,
+        timing_error: None,
+        insecure: false,
+        certificate: None,
+        ip_version: None,
+        resolved: Vec::new(),
+        throttled: false,
+    }
+}
+/// Builds the TLS connector of timing traces, trusting the certificates of
+/// `options`.
+fn tls_connector(options: &CheckOptions) -> Result<native_tls::TlsConnector, PantherError> {
+    let mut builder = native_tls::TlsConnector::builder();
+    builder.danger_accept_invalid_certs(options.insecure);
+    for pem in &options.ca_certs {
+        for cert in pem_certificates(pem) {
+            builder.add_root_certificate(
+                native_tls::Certificate::from_pem(cert).map_err(io::Error::other)?,
+            );
+        }
+    }
+    Ok(builder.build().map_err(io::Error::other)?)
+}
+/// Splits a PEM bundle into its certificates, `native_tls` only reading the
+/// first one of a bundle.
+fn pem_certificates(pem: &[u8]) -> Vec<&[u8]> {
+    const END: &[u8] = b"-----END CERTIFICATE-----";
+    let mut certs = Vec::new();
+    let mut rest = pem;
+    while let Some(end) = rest.windows(END.len()).position(|window| window == END) {
+        let (cert, tail) = rest.split_at(end + END.len());
+        certs.push(cert);
+        rest = tail;
+    }
+    certs
+}
+/// A response to a single request.
+struct Probe {
+    status: StatusCode,
+    ttfb: Duration,
+    total: Duration,
+    capture: Option<Capture>,
+    redirects: Vec<String>,
+    certificate: Option<CertificateInfo>,
+    retry_after: Option<Duration>,
+    throttled: bool,
+}
+/// Headers carrying credentials, dropped on redirects to another origin.
+const SENSITIVE_HEADERS: [HeaderName; 4] =
+    [AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION, WWW_AUTHENTICATE];
+/// How many redirects a probe follows before giving up, as many as reqwest
+/// follows by default.
+const MAX_REDIRECTS: usize = 10;
+/// Returns whether `status` is how a server says it does not support `HEAD`.
+fn is_head_rejection(status: StatusCode) -> bool {
+    status == StatusCode::METHOD_NOT_ALLOWED || status == StatusCode::NOT_IMPLEMENTED
+}
+
+/// Appends a probe `path` to `url`, treating `/` as the URL itself.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::join_path;
+///
+/// assert_eq!(join_path("https://example.com/", "/"), "https://example.com/");
+/// assert_eq!(
+///     join_path("https://example.com/", "/latest"),
+///     "https://example.com/latest"
+/// );
+/// ```
+pub fn join_path(url: &str, path: &str) -> String {
+    if path == "/" {
+        url.to_string()
+    } else {
+        format!("{}{}", url.trim_end_matches('/'), path)
+    }
+}
+/// Maps an HTTP status to a [`CheckStatus`], given the codes `accepted` as
+/// available.
+fn classify(status: StatusCode, accepted: &StatusSet) -> CheckStatus {
+    if accepted.contains(status) {
+        CheckStatus::Available
+    } else if status.is_server_error() {
+        CheckStatus::NotAvailable
+    } else {
+        CheckStatus::Unexpected
+    }
+}
@@ -0,0 +1,601 @@
+use crate::config::Config;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use globset::{Glob, GlobMatcher};
+use panther::{
+    CheckOptions, DnsServer, ExtensionFilter, HostLimit, IpVersion, NsfwFilter, RetryPolicy,
+    StatusSet, DEFAULT_INDEX_URL, DEFAULT_REPO_URL,
+};
+use regex::Regex;
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT},
+    Method,
+};
+use std::{path::PathBuf, time::Duration};
+
+/// Bulk-validates manga source URLs from a Tachiyomi extension index.
+#[derive(Parser, Debug)]
+#[command(name = "panther", version, about)]
+pub struct Cli {
+    /// Never write anything to disk: indexes are kept in memory and results
+    /// only go to stdout.
+    #[arg(long, global = true)]
+    pub read_only: bool,
+    /// Exit with 0 even when some sources (or mirrored files) fail. Errors
+    /// of panther itself still exit with 2.
+    #[arg(long, global = true)]
+    pub no_fail: bool,
+    /// Send every request through this HTTP or SOCKS5 proxy
+    /// (e.g. "socks5://127.0.0.1:1080"). HTTP_PROXY, HTTPS_PROXY and
+    /// ALL_PROXY are honored without it.
+    #[arg(long, global = true, value_parser = parse_proxy)]
+    pub proxy: Option<String>,
+    /// Also trust the certificate authorities in this PEM file, for sources
+    /// with a private or self-signed chain. Can be repeated.
+    #[arg(long, global = true, value_name = "PATH", value_parser = parse_ca_bundle)]
+    pub cacert: Vec<CaBundle>,
+    /// Skip certificate verification entirely, to learn whether sources with
+    /// a broken chain respond at all. Such responses are flagged with a
+    /// warning.
+    #[arg(long, global = true)]
+    pub insecure: bool,
+    /// Resolve the hosts of sources with this nameserver (e.g. "1.1.1.1")
+    /// or DNS over HTTPS endpoint (e.g. "https://cloudflare-dns.com/dns-query")
+    /// instead of the system resolver, which some ISPs use to block sites.
+    #[arg(long, global = true, value_name = "SERVER", default_value = "system")]
+    pub dns: DnsServer,
+    /// Only print failures, without summaries or progress bar.
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+    /// Also print the redirect chain and response headers of each check.
+    #[arg(short, long, global = true, conflicts_with = "quiet")]
+    pub verbose: bool,
+    /// Print without colors, for logs. Colors are also left out when the
+    /// NO_COLOR environment variable is set.
+    #[arg(long, global = true)]
+    pub no_color: bool,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Download the extension index to a local file.
+    Download {
+        /// URL of the remote index.
+        #[arg(long, default_value = DEFAULT_INDEX_URL)]
+        index_url: String,
+        /// Where to save the downloaded index.
+        #[arg(short, long, default_value = "index.min.json")]
+        output: PathBuf,
+    },
+    /// Check the availability of every source in the index.
+    Check(Box<CheckArgs>),
+    /// List the sources in the index without checking them.
+    List {
+        #[command(flatten)]
+        index: IndexArgs,
+    },
+    /// Print monitor definitions for an external uptime tool, one per
+    /// source of the index.
+    Export {
+        /// The uptime tool to generate definitions for.
+        #[arg(short, long, value_enum)]
+        format: ExportFormat,
+        /// How often the monitors check their source (e.g. "5m").
+        #[arg(long, default_value = "5m", value_parser = parse_duration)]
+        interval: Duration,
+        #[command(flatten)]
+        index: IndexArgs,
+    },
+    /// Report how many extensions and sources of each repository leave
+    /// optional metadata out, such as icons, version codes or languages.
+    Coverage {
+        #[command(flatten)]
+        index: IndexArgs,
+    },
+    /// Check a candidate base URL for a source side by side with its current
+    /// one, on every probe path, before swapping them in the index. Fails if
+    /// the candidate is down where the current URL is up.
+    TryReplace {
+        /// Id of the source to replace.
+        source_id: String,
+        /// The candidate base URL.
+        new_url: String,
+        /// Read settings, such as accepted status codes and headers, from
+        /// this TOML file.
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// How long a single request may take, body included (e.g. "10s").
+        #[arg(short, long, default_value = "30s", value_parser = parse_duration)]
+        timeout: Duration,
+        /// Path compared on both URLs. Can be given several times; replaces
+        /// the paths of the config file and the default ones.
+        #[arg(long = "path")]
+        paths: Vec<String>,
+        #[command(flatten)]
+        index: IndexArgs,
+    },
+    /// Show what changed between two snapshots of an index.
+    Diff {
+        /// The older index file.
+        old: PathBuf,
+        /// The newer index file.
+        new: PathBuf,
+    },
+    /// Validate an index against the expected schema and list every
+    /// violation.
+    Validate {
+        /// Path or http(s) URL of the index to validate.
+        #[arg(default_value = "index.min.json")]
+        index: String,
+    },
+    /// Render the results of a previous check as a report.
+    Report {
+        /// Results saved from `panther check --format json`.
+        #[arg(long, default_value = "results.json")]
+        input: PathBuf,
+        /// Write a self-contained HTML page to this file. Printed to stdout
+        /// when omitted.
+        #[arg(long)]
+        html: Option<PathBuf>,
+        /// Merge notes and links from this JSON file of annotations.
+        #[arg(long)]
+        annotations: Option<PathBuf>,
+    },
+    /// Cross-check the published index against the repository it is built
+    /// from, reporting missing or orphaned APKs and icons.
+    AuditRepo {
+        /// Clone this git repository (e.g. a keiyoushi/extensions fork).
+        #[arg(long, required_unless_present = "path")]
+        git: Option<String>,
+        /// Branch of the git repository holding the published files.
+        #[arg(long, default_value = "repo")]
+        branch: String,
+        /// Audit this existing checkout instead of cloning one.
+        #[arg(long, conflicts_with = "git")]
+        path: Option<PathBuf>,
+        /// URL of the published index.
+        #[arg(long, default_value = DEFAULT_INDEX_URL)]
+        index_url: String,
+    },
+    /// Check that a mirror serves the same index and APKs as upstream.
+    VerifyMirror {
+        /// Root of the mirrored repository, holding index.min.json and apk/.
+        url: String,
+        /// Root of the upstream repository.
+        #[arg(long, default_value = DEFAULT_REPO_URL)]
+        upstream: String,
+        /// How many APKs, picked at random, to compare.
+        #[arg(long, default_value_t = 5)]
+        sample: usize,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct CheckArgs {
+    /// Check these URLs directly instead of the sources of an index.
+    pub urls: Vec<String>,
+    /// How to print the results.
+    #[arg(short, long, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+    /// Maximum number of URLs checked at the same time.
+    #[arg(short, long, default_value_t = 16)]
+    pub concurrency: usize,
+    /// Maximum number of requests to the same host at the same time, as
+    /// many sources share a domain that rate limits. 0 does not limit them.
+    #[arg(long, default_value_t = 0)]
+    pub per_host: usize,
+    /// Shortest time between the starts of two requests to the same host
+    /// (e.g. "500ms").
+    #[arg(long, default_value = "0s", value_parser = parse_duration)]
+    pub per_host_delay: Duration,
+    /// Abort the run if panther's resident memory grows past this size
+    /// (e.g. "256MB"). Only enforced on Linux.
+    #[arg(long, value_parser = parse_size)]
+    pub max_memory: Option<u64>,
+    /// How many times a request is sent at most when it keeps failing with a
+    /// transient error or a 5xx response. 1 disables retrying.
+    #[arg(long, default_value_t = 3)]
+    pub attempts: u32,
+    /// Delay before the first retry, doubled on every further retry
+    /// (e.g. "500ms", "2s").
+    #[arg(long, default_value = "500ms", value_parser = parse_duration)]
+    pub retry_delay: Duration,
+    /// Longest delay between two retries.
+    #[arg(long, default_value = "10s", value_parser = parse_duration)]
+    pub max_retry_delay: Duration,
+    /// Wait exactly the computed delay between retries instead of a random
+    /// part of it.
+    #[arg(long)]
+    pub no_jitter: bool,
+    /// How long a single request may take, body included (e.g. "10s").
+    #[arg(short, long, default_value = "30s", value_parser = parse_duration)]
+    pub timeout: Duration,
+    /// How long the whole run may take. Sources not checked by then are
+    /// reported as timed out.
+    #[arg(long, value_parser = parse_duration)]
+    pub deadline: Option<Duration>,
+    /// Status codes counted as available (e.g. "200-299,403"), for every
+    /// domain without a rule in the config file. Any 2xx by default.
+    #[arg(long)]
+    pub accept: Option<StatusSet>,
+    /// Read settings, such as per-domain accepted status codes, from this
+    /// TOML file.
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+    /// HTTP method of the requests. HEAD skips downloading bodies and falls
+    /// back to GET on servers that reject it.
+    #[arg(long, value_enum, default_value_t = HttpMethod::Get)]
+    pub method: HttpMethod,
+    /// Path probed on each URL, e.g. "/latest". Can be given several times;
+    /// replaces the paths of the config file and the default "/", "/latest"
+    /// and "/search?q=a".
+    #[arg(long = "path")]
+    pub paths: Vec<String>,
+    /// Resolve every host up front, reporting unresolvable ones right away
+    /// instead of spending a request on each of their sources.
+    #[arg(long)]
+    pub prefetch_dns: bool,
+    /// User-Agent sent with every request, as many sites block requests
+    /// without a browser-like one.
+    #[arg(long, value_parser = HeaderValue::from_str)]
+    pub user_agent: Option<HeaderValue>,
+    /// Header sent with every request, as "Name: value". Can be given
+    /// several times.
+    #[arg(short = 'H', long = "header", value_parser = parse_header)]
+    pub headers: Vec<(HeaderName, HeaderValue)>,
+    /// Abort requests still running after this many times --timeout,
+    /// reporting their check as reaped. 0 disables the watchdog.
+    #[arg(long, default_value_t = 3)]
+    pub reap_factor: u32,
+    /// How many idle connections to each host are kept open for reuse.
+    #[arg(long, default_value_t = 32)]
+    pub pool_size: usize,
+    /// How long an idle connection is kept open for reuse (e.g. "90s").
+    #[arg(long, default_value = "90s", value_parser = parse_duration)]
+    pub keep_alive: Duration,
+    /// Report how long DNS resolution, the TCP connection, the TLS handshake
+    /// and the first byte took for each URL, measured with one more request.
+    #[arg(long)]
+    pub trace_timing: bool,
+    /// Only connect over this IP version. With "both", every source is
+    /// checked over IPv4 and over IPv6, and reported once for each.
+    #[arg(long, value_enum)]
+    pub ip_version: Option<IpVersions>,
+    /// Report available URLs whose TLS certificate expires within this long
+    /// (e.g. "30d") as expiring.
+    #[arg(long, default_value = "14d", value_parser = parse_duration)]
+    pub cert_expiry_warning: Duration,
+    /// Fail this share of checks on purpose (e.g. "5%") with a simulated
+    /// timeout, 503 response or DNS failure, to make sure alerting and
+    /// dashboards react before a real outage does.
+    #[cfg(feature = "chaos")]
+    #[arg(long, value_parser = parse_percent)]
+    pub chaos: Option<f64>,
+    /// How many of the slowest hosts the latency summary printed after the
+    /// results lists. Only used by the text format.
+    #[arg(long, default_value_t = 5)]
+    pub slowest: usize,
+    /// Report progress on stderr while checking, for wrappers and
+    /// dashboards. The index is read entirely first to know how many
+    /// sources there are.
+    #[arg(long, value_enum)]
+    pub progress_format: Option<ProgressFormat>,
+    /// How often progress is reported with --progress-format (e.g. "2s").
+    #[arg(long, default_value = "2s", value_parser = parse_interval)]
+    pub progress_interval: Duration,
+    /// Don't show the progress bar drawn on stderr while checking, when
+    /// stdout is a terminal.
+    #[arg(long)]
+    pub no_progress: bool,
+    /// Merge notes and links from this JSON file of annotations into the
+    /// results. Not used by the text and ndjson formats.
+    #[arg(long)]
+    pub annotations: Option<PathBuf>,
+    /// Save the headers and the start of the body of failed responses to
+    /// this directory.
+    #[arg(long)]
+    pub capture_failures: Option<PathBuf>,
+    /// Share of failed checks to capture (e.g. "10%").
+    #[arg(long, default_value = "100%", value_parser = parse_percent, requires = "capture_failures")]
+    pub sample: f64,
+    /// How much of the body of a failed response to capture.
+    #[arg(long, default_value = "16KB", value_parser = parse_size, requires = "capture_failures")]
+    pub capture_bytes: u64,
+    #[command(flatten)]
+    pub index: IndexArgs,
+}
+
+impl CheckArgs {
+    /// Returns the headers sent with every request: those of `config`,
+    /// replaced by `--header` and `--user-agent`.
+    fn headers(&self, config: &Config) -> HeaderMap {
+        let mut headers = config.headers.clone();
+        for (name, value) in &self.headers {
+            headers.insert(name, value.clone());
+        }
+        if let Some(user_agent) = &self.user_agent {
+            headers.insert(USER_AGENT, user_agent.clone());
+        }
+        headers
+    }
+
+    /// Returns the IP versions to check sources over, `None` standing for
+    /// any of them.
+    pub fn ip_versions(&self) -> Vec<Option<IpVersion>> {
+        match self.ip_version {
+            None => vec![None],
+            Some(IpVersions::V4) => vec![Some(IpVersion::V4)],
+            Some(IpVersions::V6) => vec![Some(IpVersion::V6)],
+            Some(IpVersions::Both) => vec![Some(IpVersion::V4), Some(IpVersion::V6)],
+        }
+    }
+
+    /// Builds the library options matching these arguments and `config`.
+    pub fn options(&self, config: &Config) -> CheckOptions {
+        CheckOptions {
+            paths: config.probe_paths(&self.paths),
+            retry: RetryPolicy {
+                max_attempts: self.attempts.max(1),
+                base_delay: self.retry_delay,
+                max_delay: self.max_retry_delay,
+                jitter: !self.no_jitter,
+            },
+            timeout: Some(self.timeout),
+            deadline: self.deadline,
+            concurrency: self.concurrency,
+            host_limit: HostLimit {
+                max_in_flight: self.per_host,
+                min_delay: self.per_host_delay,
+            },
+            prefetch_dns: self.prefetch_dns,
+            reap_factor: self.reap_factor,
+            trace_timing: self.trace_timing,
+            cert_expiry_warning: self.cert_expiry_warning,
+            #[cfg(feature = "chaos")]
+            chaos: self.chaos.unwrap_or(0.0),
+            pool_max_idle_per_host: self.pool_size,
+            pool_idle_timeout: Some(self.keep_alive),
+            headers: self.headers(config),
+            domain_headers: config.domain_headers(),
+            accept: config.accept_policy(self.accept.as_ref()),
+            method: match self.method {
+                HttpMethod::Get => Method::GET,
+                HttpMethod::Head => Method::HEAD,
+            },
+            capture_bytes: match self.capture_failures {
+                Some(_) => self.capture_bytes as usize,
+                None => 0,
+            },
+            ..CheckOptions::default()
+        }
+    }
+}
+
+/// Output formats of `panther check`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Colored lines, printed as checks complete.
+    Text,
+    /// A JSON array of results, printed once every check is done.
+    Json,
+    /// One CSV row per source, printed once every check is done.
+    Csv,
+    /// One JSON object per line, printed as checks complete.
+    Ndjson,
+    /// A Markdown table grouped by extension, printed once every check is
+    /// done.
+    Markdown,
+}
+
+/// Formats of the progress reported by `panther check`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProgressFormat {
+    /// One JSON object per line: how many checks completed, remain and
+    /// failed, and the estimated time left.
+    Json,
+}
+
+/// IP versions `panther check` can connect over.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IpVersions {
+    /// IPv4 only.
+    #[value(name = "4")]
+    V4,
+    /// IPv6 only.
+    #[value(name = "6")]
+    V6,
+    /// Each of them, separately.
+    Both,
+}
+
+/// Output formats of `panther export`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// An Uptime Kuma backup, to import from its settings.
+    UptimeKuma,
+    /// The endpoints section of a Gatus configuration.
+    Gatus,
+}
+
+/// Options selecting which index to read and which of its sources to use.
+#[derive(Args, Debug)]
+pub struct IndexArgs {
+    /// Read a local index file instead of downloading one.
+    #[arg(short, long, conflicts_with = "index_url")]
+    pub index: Option<PathBuf>,
+    /// URL of the remote index.
+    #[arg(long, default_value = DEFAULT_INDEX_URL)]
+    pub index_url: String,
+    /// Use the index of this repository (the directory holding
+    /// index.min.json) instead. Can be given several times to check a fork
+    /// or a self-hosted repository along with keiyoushi.
+    #[arg(long, conflicts_with_all = ["index", "index_url"])]
+    pub repo: Vec<String>,
+    /// Where to save the downloaded index. Ignored with --read-only or
+    /// several repositories.
+    #[arg(short, long, default_value = "index.min.json")]
+    pub output: PathBuf,
+    /// Only use sources of these languages (e.g. "es,en"). "multi" selects
+    /// multi-language extensions and "all", the default, every language.
+    #[arg(short, long, value_delimiter = ',')]
+    pub lang: Vec<String>,
+    /// Whether to use extensions flagged as NSFW.
+    #[arg(long, value_enum, default_value_t = Nsfw::Include)]
+    pub nsfw: Nsfw,
+    /// Only use extensions whose name or package matches this regex, or
+    /// sources whose name or id matches it.
+    #[arg(long, value_parser = Regex::new)]
+    pub filter: Option<Regex>,
+    /// Only use extensions whose package matches this glob
+    /// (e.g. "*.es.*").
+    #[arg(long, value_parser = parse_glob)]
+    pub pkg: Option<GlobMatcher>,
+}
+
+impl IndexArgs {
+    /// Builds the library filter matching these arguments.
+    pub fn filter(&self) -> ExtensionFilter {
+        ExtensionFilter {
+            langs: self.lang.clone(),
+            nsfw: match self.nsfw {
+                Nsfw::Include => NsfwFilter::Include,
+                Nsfw::Exclude => NsfwFilter::Exclude,
+                Nsfw::Only => NsfwFilter::Only,
+            },
+            pattern: self.filter.clone(),
+            pkg: self.pkg.clone(),
+        }
+    }
+}
+
+/// Values of `--method`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HttpMethod {
+    Get,
+    Head,
+}
+
+/// Values of `--nsfw`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Nsfw {
+    /// Use NSFW extensions along with the others.
+    Include,
+    /// Skip NSFW extensions.
+    Exclude,
+    /// Only use NSFW extensions.
+    Only,
+}
+
+/// Compiles a glob such as `*.es.*`.
+fn parse_glob(value: &str) -> Result<GlobMatcher, globset::Error> {
+    Ok(Glob::new(value)?.compile_matcher())
+}
+
+/// Parses a header such as `Referer: https://example.com/`.
+fn parse_header(value: &str) -> Result<(HeaderName, HeaderValue), String> {
+    let (name, value) = value
+        .split_once(':')
+        .ok_or_else(|| "expected \"Name: value\"".to_string())?;
+    let name = HeaderName::from_bytes(name.trim().as_bytes()).map_err(|e| e.to_string())?;
+    let value = HeaderValue::from_str(value.trim()).map_err(|e| e.to_string())?;
+    Ok((name, value))
+}
+
+/// Checks that `value` is a proxy URL reqwest can use.
+fn parse_proxy(value: &str) -> Result<String, String> {
+    reqwest::Proxy::all(value)
+        .map(|_| value.to_string())
+        .map_err(|_| "expected a URL such as http://host:port or socks5://host:port".to_string())
+}
+
+/// The content of a PEM file of certificate authorities.
+#[derive(Debug, Clone)]
+pub struct CaBundle(pub Vec<u8>);
+
+/// Reads the PEM file at `path`, checking it holds at least one certificate.
+fn parse_ca_bundle(path: &str) -> Result<CaBundle, String> {
+    let pem = std::fs::read(path).map_err(|e| e.to_string())?;
+    match reqwest::Certificate::from_pem_bundle(&pem) {
+        Ok(certs) if !certs.is_empty() => Ok(CaBundle(pem)),
+        _ => Err("expected PEM encoded certificates".to_string()),
+    }
+}
+
+/// Parses a size such as `512`, `64K`, `256MB` or `1G` into bytes.
+fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid size: {}", value))?;
+    let multiplier = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        _ => return Err(format!("unknown size unit: {}", unit)),
+    };
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("size too large: {}", value))
+}
+
+/// Parses a percentage such as `10%` into a fraction. The `%` is optional.
+fn parse_percent(value: &str) -> Result<f64, String> {
+    let value = value.trim();
+    let percent: f64 = value
+        .strip_suffix('%')
+        .unwrap_or(value)
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid percentage: {}", value))?;
+    if (0.0..=100.0).contains(&percent) {
+        Ok(percent / 100.0)
+    } else {
+        Err(format!("percentage out of range: {}", value))
+    }
+}
+
+/// Parses a duration such as `250ms`, `10s`, `5m`, `1h` or `14d`. A bare number is
+/// taken as seconds.
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration: {}", value))?;
+    let seconds = |multiplier: u64| {
+        number
+            .checked_mul(multiplier)
+            .map(Duration::from_secs)
+            .ok_or_else(|| format!("duration too long: {}", value))
+    };
+    match unit.trim() {
+        "ms" => Ok(Duration::from_millis(number)),
+        "" | "s" => Ok(Duration::from_secs(number)),
+        "m" => seconds(60),
+        "h" => seconds(60 * 60),
+        "d" => seconds(24 * 60 * 60),
+        _ => Err(format!("unknown duration unit: {}", unit)),
+    }
+}
+
+/// Parses how often something repeats, like [`parse_duration`] but
+/// rejecting zero and durations too long to schedule.
+fn parse_interval(value: &str) -> Result<Duration, String> {
+    let interval = parse_duration(value)?;
+    if interval.is_zero() {
+        return Err("interval must be longer than zero".to_string());
+    }
+    match tokio::time::Instant::now().checked_add(interval) {
+        Some(_) => Ok(interval),
+        None => Err(format!("interval too long: {}", value.trim())),
+    }
+}
@@ -0,0 +1,100 @@
+use panther::{AcceptPolicy, StatusSet, PROBE_PATHS};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::{Deserialize, Deserializer};
+use std::{collections::HashMap, fs, path::Path};
+
+/// Settings read from the TOML file given with `--config`.
+///
+/// ```toml
+/// accept = "200-299"
+/// paths = ["/", "/latest"]
+///
+/// [headers]
+/// User-Agent = "Mozilla/5.0"
+///
+/// [domains."example.com"]
+/// accept = "200-299,403"
+/// headers = { Referer = "https://example.com/" }
+/// ```
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Status codes counted as available, unless `--accept` is given.
+    pub accept: Option<StatusSet>,
+    /// Paths probed on each URL, unless `--path` is given.
+    pub paths: Option<Vec<String>>,
+    /// Headers sent with every request, under those given with `--header`.
+    #[serde(default, deserialize_with = "headers")]
+    pub headers: HeaderMap,
+    /// Settings for a domain and its subdomains.
+    #[serde(default)]
+    pub domains: HashMap<String, DomainConfig>,
+}
+
+/// Settings for a single domain.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct DomainConfig {
+    /// Status codes counted as available for this domain.
+    pub accept: Option<StatusSet>,
+    /// Headers sent to this domain, replacing those of the same name.
+    #[serde(default, deserialize_with = "headers")]
+    pub headers: HeaderMap,
+}
+
+impl Config {
+    /// Reads the config file at `path`.
+    pub fn load(path: &Path) -> Result<Config, Box<dyn std::error::Error>> {
+        let text = fs::read_to_string(path)?;
+        toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+
+    /// Builds the acceptance policy of this config, with `accept` replacing
+    /// its default codes if given.
+    pub fn accept_policy(&self, accept: Option<&StatusSet>) -> AcceptPolicy {
+        AcceptPolicy {
+            default: accept.or(self.accept.as_ref()).cloned().unwrap_or_default(),
+            domains: self
+                .domains
+                .iter()
+                .filter_map(|(domain, config)| {
+                    let accept = config.accept.clone()?;
+                    Some((domain.to_ascii_lowercase(), accept))
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the paths to probe on each URL: `paths` if any are given,
+    /// else those of this config, else [`PROBE_PATHS`].
+    pub fn probe_paths(&self, paths: &[String]) -> Vec<String> {
+        if !paths.is_empty() {
+            paths.to_vec()
+        } else if let Some(paths) = &self.paths {
+            paths.clone()
+        } else {
+            PROBE_PATHS.iter().map(|path| path.to_string()).collect()
+        }
+    }
+
+    /// Returns the headers of every domain with some, keyed by lower case
+    /// domain.
+    pub fn domain_headers(&self) -> HashMap<String, HeaderMap> {
+        self.domains
+            .iter()
+            .filter(|(_, config)| !config.headers.is_empty())
+            .map(|(domain, config)| (domain.to_ascii_lowercase(), config.headers.clone()))
+            .collect()
+    }
+}
+
+/// Deserializes a table of header names and values.
+fn headers<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HeaderMap, D::Error> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in HashMap::<String, String>::deserialize(deserializer)? {
+        let name = HeaderName::from_bytes(name.as_bytes()).map_err(serde::de::Error::custom)?;
+        let value = HeaderValue::from_str(&value).map_err(serde::de::Error::custom)?;
+        headers.insert(name, value);
+    }
+    Ok(headers)
+}
@@ -0,0 +1,107 @@
+use crate::{Extension, Source};
+
+/// How well a field is filled in across an index, found by
+/// [`field_coverage`].
+///
+/// # Fields
+///
+/// * `field`: The name of the field in the index, prefixed with `sources[].`
+///   for fields of sources (e.g. `sources[].versionId`).
+/// * `missing`: How many extensions, or sources, lack a meaningful value:
+///   the field is absent, empty, or zero for version codes.
+/// * `total`: How many extensions, or sources, were looked at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldCoverage {
+    pub field: &'static str,
+    pub missing: usize,
+    pub total: usize,
+}
+
+impl FieldCoverage {
+    /// Returns the share of extensions or sources filling in the field, in
+    /// percent, 100 when there were none.
+    pub fn percent(&self) -> f64 {
+        match self.total {
+            0 => 100.0,
+            total => (total - self.missing) as f64 * 100.0 / total as f64,
+        }
+    }
+}
+
+/// A field of `T`, with how to tell that it is missing.
+type Field<T> = (&'static str, fn(&T) -> bool);
+
+/// Fields of extensions.
+const EXTENSION_FIELDS: &[Field<Extension>] = &[
+    ("sources", |e| e.sources.is_empty()),
+    ("lang", |e| e.lang.trim().is_empty()),
+    ("code", |e| e.code <= 0),
+    ("version", |e| e.version.trim().is_empty()),
+    ("icon", |e| e.icon.as_deref().is_none_or(str::is_empty)),
+    ("hasReadme", |e| e.has_readme.is_none()),
+    ("hasChangelog", |e| e.has_changelog.is_none()),
+];
+
+/// Fields of sources.
+const SOURCE_FIELDS: &[Field<Source>] = &[
+    ("sources[].name", |s| s.name.trim().is_empty()),
+    ("sources[].lang", |s| s.lang.trim().is_empty()),
+    ("sources[].baseUrl", |s| s.base_url.trim().is_empty()),
+    ("sources[].versionId", |s| s.version_id.is_none()),
+];
+
+/// Counts, for each optional or often neglected field of the index, how many
+/// extensions or sources leave it out.
+///
+/// Unlike [`crate::validate_index`], which rejects what breaks the schema,
+/// this measures the quality of metadata that parses fine but is not much
+/// use: extensions without sources, empty languages, zero version codes...
+/// Fields of extensions come first, then fields of sources.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::{field_coverage, Extension};
+///
+/// let extensions: Vec<Extension> = serde_json::from_str(r#"[
+///     {"name": "A", "pkg": "a", "apk": "a.apk", "lang": "en", "code": 0, "version": "1.4.1", "nsfw": 0,
+///      "sources": [{"name": "A", "lang": "", "id": "1", "baseUrl": "https://a.com"}]},
+///     {"name": "B", "pkg": "b", "apk": "b.apk", "lang": "en", "code": 3, "version": "1.4.3", "nsfw": 0}
+/// ]"#).unwrap();
+///
+/// let coverage = field_coverage(&extensions);
+/// let code = coverage.iter().find(|c| c.field == "code").unwrap();
+/// assert_eq!((code.missing, code.total), (1, 2));
+/// let sources = coverage.iter().find(|c| c.field == "sources").unwrap();
+/// assert_eq!(sources.missing, 1);
+/// let lang = coverage.iter().find(|c| c.field == "sources[].lang").unwrap();
+/// assert_eq!(lang.percent(), 0.0);
+/// ```
+pub fn field_coverage<'a, I>(extensions: I) -> Vec<FieldCoverage>
+where
+    I: IntoIterator<Item = &'a Extension>,
+{
+    let fields = EXTENSION_FIELDS.iter().map(|(field, _)| *field);
+    let fields = fields.chain(SOURCE_FIELDS.iter().map(|(field, _)| *field));
+    let mut coverage: Vec<FieldCoverage> = fields
+        .map(|field| FieldCoverage {
+            field,
+            missing: 0,
+            total: 0,
+        })
+        .collect();
+    let (extension_coverage, source_coverage) = coverage.split_at_mut(EXTENSION_FIELDS.len());
+    for extension in extensions {
+        for ((_, missing), coverage) in EXTENSION_FIELDS.iter().zip(&mut *extension_coverage) {
+            coverage.total += 1;
+            coverage.missing += usize::from(missing(extension));
+        }
+        for source in &extension.sources {
+            for ((_, missing), coverage) in SOURCE_FIELDS.iter().zip(&mut *source_coverage) {
+                coverage.total += 1;
+                coverage.missing += usize::from(missing(source));
+            }
+        }
+    }
+    coverage
+}
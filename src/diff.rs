@@ -0,0 +1,102 @@
+use crate::Extension;
+use std::collections::BTreeMap;
+
+/// A change between two snapshots of an index, found by [`diff_indexes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexChange {
+    /// An extension only found in the new index.
+    Added {
+        pkg: String,
+        name: String,
+        version: String,
+    },
+    /// An extension only found in the old index.
+    Removed { pkg: String, name: String },
+    /// An extension whose version changed.
+    VersionChanged {
+        pkg: String,
+        name: String,
+        old: String,
+        new: String,
+    },
+    /// A source, found in both indexes by id, whose base URL changed.
+    BaseUrlChanged {
+        pkg: String,
+        source: String,
+        id: String,
+        old: String,
+        new: String,
+    },
+}
+
+/// Compares two snapshots of an index.
+///
+/// Extensions are matched by package name and sources by id. Changes are
+/// sorted by package name, with every change of a package together.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::{diff_indexes, Extension, IndexChange};
+///
+/// let old: Vec<Extension> = serde_json::from_str(r#"[
+///     {"name": "A", "pkg": "a", "apk": "a.apk", "lang": "en", "code": 1, "version": "1.4.1", "nsfw": 0,
+///      "sources": [{"name": "A", "lang": "en", "id": "1", "baseUrl": "https://a.com"}]}
+/// ]"#).unwrap();
+/// let new: Vec<Extension> = serde_json::from_str(r#"[
+///     {"name": "A", "pkg": "a", "apk": "a.apk", "lang": "en", "code": 2, "version": "1.4.2", "nsfw": 0,
+///      "sources": [{"name": "A", "lang": "en", "id": "1", "baseUrl": "https://a.net"}]}
+/// ]"#).unwrap();
+///
+/// let changes = diff_indexes(&old, &new);
+/// assert_eq!(changes.len(), 2);
+/// assert!(matches!(&changes[1], IndexChange::BaseUrlChanged { new, .. } if new == "https://a.net"));
+/// ```
+pub fn diff_indexes(old: &[Extension], new: &[Extension]) -> Vec<IndexChange> {
+    let old: BTreeMap<&str, &Extension> = old.iter().map(|e| (e.pkg.as_str(), e)).collect();
+    let new: BTreeMap<&str, &Extension> = new.iter().map(|e| (e.pkg.as_str(), e)).collect();
+    let mut pkgs: Vec<&str> = old.keys().chain(new.keys()).copied().collect();
+    pkgs.sort_unstable();
+    pkgs.dedup();
+
+    let mut changes = Vec::new();
+    for pkg in pkgs {
+        match (old.get(pkg), new.get(pkg)) {
+            (None, Some(new)) => changes.push(IndexChange::Added {
+                pkg: pkg.to_string(),
+                name: new.name.clone(),
+                version: new.version.clone(),
+            }),
+            (Some(old), None) => changes.push(IndexChange::Removed {
+                pkg: pkg.to_string(),
+                name: old.name.clone(),
+            }),
+            (Some(old), Some(new)) => {
+                if old.version != new.version {
+                    changes.push(IndexChange::VersionChanged {
+                        pkg: pkg.to_string(),
+                        name: new.name.clone(),
+                        old: old.version.clone(),
+                        new: new.version.clone(),
+                    });
+                }
+                for source in &new.sources {
+                    let Some(previous) = old.sources.iter().find(|s| s.id == source.id) else {
+                        continue;
+                    };
+                    if previous.base_url != source.base_url {
+                        changes.push(IndexChange::BaseUrlChanged {
+                            pkg: pkg.to_string(),
+                            source: source.name.clone(),
+                            id: source.id.clone(),
+                            old: previous.base_url.clone(),
+                            new: source.base_url.clone(),
+                        });
+                    }
+                }
+            }
+            (None, None) => unreachable!("every package comes from one of the indexes"),
+        }
+    }
+    changes
+}
@@ -0,0 +1,212 @@
+use crate::PantherError;
+use futures::future;
+use hickory_proto::{
+    op::{Message, Query, ResponseCode},
+    rr::{Name, RData, RecordType},
+};
+use hickory_resolver::{
+    config::{NameServerConfigGroup, ResolverConfig},
+    name_server::TokioConnectionProvider,
+    TokioResolver,
+};
+use reqwest::{
+    header::{ACCEPT, CONTENT_TYPE},
+    Client,
+};
+use std::{
+    fmt, io,
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+/// Where host names are resolved.
+///
+/// ISPs commonly block sites by answering DNS queries for them with a bogus
+/// address, so the system resolver can report a working source as dead.
+///
+/// # Variants
+///
+/// * `System`: The resolver of the operating system.
+/// * `Plain`: A nameserver queried directly, over UDP with a TCP fallback.
+/// * `Https`: A DNS over HTTPS endpoint, such as
+///   `https://cloudflare-dns.com/dns-query`.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::DnsServer;
+///
+/// assert_eq!("system".parse::<DnsServer>().unwrap(), DnsServer::System);
+/// assert_eq!(
+///     "1.1.1.1".parse::<DnsServer>().unwrap(),
+///     DnsServer::Plain("1.1.1.1:53".parse().unwrap())
+/// );
+/// assert!(matches!(
+///     "https://dns.google/dns-query".parse::<DnsServer>().unwrap(),
+///     DnsServer::Https(_)
+/// ));
+/// assert!("dns.google".parse::<DnsServer>().is_err());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DnsServer {
+    #[default]
+    System,
+    Plain(SocketAddr),
+    Https(String),
+}
+
+impl FromStr for DnsServer {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if value == "system" {
+            return Ok(DnsServer::System);
+        }
+        if value.starts_with("https://") {
+            return reqwest::Url::parse(value)
+                .map(|_| DnsServer::Https(value.to_string()))
+                .map_err(|e| e.to_string());
+        }
+        if let Ok(addr) = value.parse::<SocketAddr>() {
+            return Ok(DnsServer::Plain(addr));
+        }
+        value
+            .parse::<IpAddr>()
+            .map(|ip| DnsServer::Plain(SocketAddr::new(ip, 53)))
+            .map_err(|_| "expected \"system\", an IP address or an https:// URL".to_string())
+    }
+}
+
+impl fmt::Display for DnsServer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DnsServer::System => f.write_str("system"),
+            DnsServer::Plain(addr) => write!(f, "{}", addr),
+            DnsServer::Https(url) => f.write_str(url),
+        }
+    }
+}
+
+/// How long a nameserver may take to answer a single query.
+const NAMESERVER_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Resolves host names with a [`DnsServer`].
+#[derive(Debug, Clone)]
+pub(crate) enum Lookup {
+    System,
+    Plain(Arc<TokioResolver>),
+    Https { client: Client, url: String },
+}
+
+impl Lookup {
+    /// Prepares to resolve with `server`. DNS over HTTPS queries go through
+    /// `proxy`, if any, like every other request.
+    pub(crate) fn new(server: &DnsServer, proxy: Option<&str>) -> Result<Self, PantherError> {
+        Ok(match server {
+            DnsServer::System => Lookup::System,
+            DnsServer::Plain(addr) => {
+                let servers =
+                    NameServerConfigGroup::from_ips_clear(&[addr.ip()], addr.port(), true);
+                let config = ResolverConfig::from_parts(None, Vec::new(), servers);
+                let provider = TokioConnectionProvider::default();
+                let mut builder = TokioResolver::builder_with_config(config, provider);
+                // The defaults retry for longer than most checks may take.
+                builder.options_mut().timeout = NAMESERVER_TIMEOUT;
+                builder.options_mut().attempts = 1;
+                Lookup::Plain(Arc::new(builder.build()))
+            }
+            DnsServer::Https(url) => {
+                let mut builder = Client::builder();
+                if let Some(proxy) = proxy {
+                    builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+                }
+                Lookup::Https {
+                    client: builder.build()?,
+                    url: url.clone(),
+                }
+            }
+        })
+    }
+
+    /// Resolves `host` to its addresses, IPv4 and IPv6 alike.
+    pub(crate) async fn lookup(&self, host: &str) -> io::Result<Vec<IpAddr>> {
+        let addrs: Vec<IpAddr> = match self {
+            Lookup::System => tokio::net::lookup_host((host, 0))
+                .await?
+                .map(|addr| addr.ip())
+                .collect(),
+            Lookup::Plain(resolver) => resolver
+                .lookup_ip(host)
+                .await
+                // Kept apart from request timeouts, the nameserver being slow
+                // or unreachable is a failure to resolve.
+                .map_err(|e| io::Error::new(io::ErrorKind::NotFound, e.to_string()))?
+                .iter()
+                .collect(),
+            Lookup::Https { client, url } => {
+                let (v4, v6) = future::join(
+                    query(client, url, host, RecordType::A),
+                    query(client, url, host, RecordType::AAAA),
+                )
+                .await;
+                // Hosts with a single kind of address are common.
+                match (v4, v6) {
+                    (Err(e), Err(_)) => return Err(e),
+                    (v4, v6) => v4
+                        .unwrap_or_default()
+                        .into_iter()
+                        .chain(v6.unwrap_or_default())
+                        .collect(),
+                }
+            }
+        };
+        if addrs.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "no address found"));
+        }
+        Ok(addrs)
+    }
+}
+
+/// Asks the DNS over HTTPS endpoint at `url` for the `record_type` addresses
+/// of `host`, as described by RFC 8484.
+async fn query(
+    client: &Client,
+    url: &str,
+    host: &str,
+    record_type: RecordType,
+) -> io::Result<Vec<IpAddr>> {
+    let name = Name::from_utf8(host).map_err(io::Error::other)?;
+    let mut message = Message::new();
+    message
+        .set_recursion_desired(true)
+        .add_query(Query::query(name, record_type));
+    let body = client
+        .post(url)
+        .header(CONTENT_TYPE, "application/dns-message")
+        .header(ACCEPT, "application/dns-message")
+        .body(message.to_vec().map_err(io::Error::other)?)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(io::Error::other)?
+        .bytes()
+        .await
+        .map_err(io::Error::other)?;
+    let answer = Message::from_vec(&body).map_err(io::Error::other)?;
+    match answer.response_code() {
+        ResponseCode::NoError | ResponseCode::NXDomain => {}
+        code => return Err(io::Error::other(format!("DNS server answered {}", code))),
+    }
+    Ok(answer
+        .answers()
+        .iter()
+        .filter_map(|record| match record.data() {
+            RData::A(a) => Some(IpAddr::V4(a.0)),
+            RData::AAAA(aaaa) => Some(IpAddr::V6(aaaa.0)),
+            _ => None,
+        })
+        .collect())
+}
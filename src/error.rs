@@ -0,0 +1,91 @@
+use reqwest::StatusCode;
+use std::{error::Error as _, io, time::Duration};
+use thiserror::Error;
+
+/// Errors returned by panther.
+///
+/// Request errors are split by cause so callers can decide what to do about
+/// them without inspecting messages: see [`PantherError::is_retryable`].
+///
+/// # Variants
+///
+/// * `NotFound`: The server answered `404 Not Found`.
+/// * `Http`: The server answered with another non-success status.
+/// * `Dns`: The host name could not be resolved.
+/// * `Unresolved`: The host name could not be resolved ahead of the request,
+///   when resolving hosts up front.
+/// * `Timeout`: The request did not complete in time.
+/// * `Deadline`: The run deadline passed before the check could finish.
+/// * `Reaped`: A request hung well past its timeout and was aborted by the
+///   watchdog.
+/// * `Request`: Any other request failure (refused connection, TLS, invalid
+///   URL...).
+/// * `Io`: Reading or writing a local file failed.
+/// * `Json`: An index could not be parsed.
+/// * `Csv`: A CSV report could not be written.
+#[derive(Debug, Error)]
+pub enum PantherError {
+    #[error("Resource not found")]
+    NotFound,
+    #[error("HTTP error: {0}")]
+    Http(StatusCode),
+    #[error("DNS resolution failed: {0}")]
+    Dns(#[source] reqwest::Error),
+    #[error("could not resolve {host}: {reason}")]
+    Unresolved { host: String, reason: String },
+    #[error("request timed out: {0}")]
+    Timeout(#[source] reqwest::Error),
+    #[error("run deadline exceeded")]
+    Deadline,
+    #[error("request hung and was reaped after {0:?}")]
+    Reaped(Duration),
+    #[error(transparent)]
+    Request(reqwest::Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+}
+
+impl PantherError {
+    /// Returns whether trying again later might succeed.
+    ///
+    /// Timeouts, failed connections and server errors are usually transient;
+    /// a missing resource, a bad index or a local I/O problem are not. DNS
+    /// failures are retryable too, since resolvers do fail intermittently,
+    /// except for `Unresolved` hosts, which are given up on by design.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            PantherError::Dns(_) | PantherError::Timeout(_) => true,
+            PantherError::Request(e) => e.is_connect() || e.is_request() || e.is_body(),
+            PantherError::Http(status) => status.is_server_error(),
+            PantherError::NotFound
+            | PantherError::Unresolved { .. }
+            | PantherError::Deadline
+            | PantherError::Reaped(_)
+            | PantherError::Io(_)
+            | PantherError::Json(_)
+            | PantherError::Csv(_) => false,
+        }
+    }
+}
+
+impl From<reqwest::Error> for PantherError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            return PantherError::Timeout(e);
+        }
+        // reqwest has no dedicated flag for name resolution failures; the
+        // connector reports them as a "dns error" somewhere down the chain.
+        let mut source = e.source();
+        while let Some(cause) = source {
+            if cause.to_string().starts_with("dns error") {
+                return PantherError::Dns(e);
+            }
+            source = cause.source();
+        }
+        PantherError::Request(e)
+    }
+}
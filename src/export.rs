@@ -0,0 +1,118 @@
+use crate::{PantherError, Target};
+use serde_json::json;
+use std::{io::Write, time::Duration};
+
+/// Writes a [Gatus](https://github.com/TwiN/gatus) configuration with one
+/// endpoint per target, grouped by extension and expecting a success status.
+///
+/// Endpoints are named after their source and language, so the different
+/// languages of a multi-language extension stay apart.
+///
+/// # Errors
+///
+/// * If writing to `writer` fails.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::{write_gatus, Target};
+/// use std::time::Duration;
+///
+/// let targets = vec![Target {
+///     url: "https://example.com".to_string(),
+///     extension: Some("My Extension".to_string()),
+///     source: Some("My Source".to_string()),
+///     lang: Some("en".to_string()),
+///     ..Target::default()
+/// }];
+/// let mut out = Vec::new();
+/// write_gatus(&mut out, &targets, Duration::from_secs(300)).unwrap();
+/// let yaml = String::from_utf8(out).unwrap();
+/// assert!(yaml.contains("  - name: \"My Source (en)\"\n    group: \"My Extension\"\n"));
+/// assert!(yaml.contains("    interval: 300s\n"));
+/// ```
+pub fn write_gatus<W: Write>(
+    mut writer: W,
+    targets: &[Target],
+    interval: Duration,
+) -> Result<(), PantherError> {
+    writeln!(writer, "endpoints:")?;
+    for target in targets {
+        // JSON strings are valid double-quoted YAML scalars.
+        writeln!(writer, "  - name: {}", json!(monitor_name(target)))?;
+        if let Some(extension) = &target.extension {
+            writeln!(writer, "    group: {}", json!(extension))?;
+        }
+        writeln!(writer, "    url: {}", json!(target.url))?;
+        writeln!(writer, "    interval: {}s", interval.as_secs().max(1))?;
+        writeln!(writer, "    conditions:")?;
+        writeln!(writer, "      - \"[STATUS] < 300\"")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes an [Uptime Kuma](https://github.com/louislam/uptime-kuma) backup
+/// with one HTTP monitor per target, to import from its settings.
+///
+/// # Errors
+///
+/// * If serializing or writing to `writer` fails.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::{write_uptime_kuma, Target};
+/// use std::time::Duration;
+///
+/// let targets = vec![Target::from("https://example.com".to_string())];
+/// let mut out = Vec::new();
+/// write_uptime_kuma(&mut out, &targets, Duration::from_secs(300)).unwrap();
+/// let backup: serde_json::Value = serde_json::from_slice(&out).unwrap();
+/// assert_eq!(backup["monitorList"][0]["url"], "https://example.com");
+/// assert_eq!(backup["monitorList"][0]["interval"], 300);
+/// ```
+pub fn write_uptime_kuma<W: Write>(
+    mut writer: W,
+    targets: &[Target],
+    interval: Duration,
+) -> Result<(), PantherError> {
+    let interval = interval.as_secs().max(20);
+    let monitors: Vec<_> = targets
+        .iter()
+        .enumerate()
+        .map(|(i, target)| {
+            json!({
+                "id": i + 1,
+                "name": monitor_name(target),
+                "description": target.extension,
+                "type": "http",
+                "url": target.url,
+                "method": "GET",
+                "interval": interval,
+                "retryInterval": interval,
+                "maxretries": 2,
+                "accepted_statuscodes": ["200-299"],
+                "active": true,
+            })
+        })
+        .collect();
+    let backup = json!({
+        "version": "1.23.0",
+        "notificationList": [],
+        "monitorList": monitors,
+    });
+    serde_json::to_writer_pretty(&mut writer, &backup)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Names the monitor of `target` after its source and language, falling back
+/// to its URL.
+fn monitor_name(target: &Target) -> String {
+    match (&target.source, &target.lang) {
+        (Some(source), Some(lang)) => format!("{} ({})", source, lang),
+        (Some(source), None) => source.clone(),
+        (None, _) => target.url.clone(),
+    }
+}
@@ -0,0 +1,108 @@
+use crate::Extension;
+use globset::GlobMatcher;
+use regex::Regex;
+
+/// Selects which extensions of an index, and which of their sources, are
+/// used.
+///
+/// # Fields
+///
+/// * `langs`: Languages to keep (e.g. `"es"`). Empty, or containing `"all"`,
+///   keeps every language. `"multi"` keeps the multi-language extensions,
+///   which the keiyoushi index lists under the `"all"` language. A source is
+///   kept if its own language is listed, so only the Spanish sources of a
+///   multi-language extension are kept with `es`. Sources whose language is
+///   `"all"` are kept for any language.
+/// * `nsfw`: Whether NSFW extensions are kept, skipped or the only ones kept.
+/// * `pattern`: Keeps the extensions whose name or package matches, and
+///   otherwise only the sources whose name or id matches.
+/// * `pkg`: Keeps only the extensions whose package matches this glob
+///   (e.g. `eu.kanade.tachiyomi.extension.es.*`).
+///
+/// # Example
+///
+/// ```rust
+/// use panther::{Extension, ExtensionFilter};
+///
+/// let json_str = r#"{
+///     "name": "Multi", "pkg": "p", "apk": "a.apk", "lang": "all",
+///     "code": 1, "version": "1.0", "nsfw": 0,
+///     "sources": [
+///         {"name": "ES", "lang": "es", "id": "1", "baseUrl": "https://es.example.com"},
+///         {"name": "EN", "lang": "en", "id": "2", "baseUrl": "https://en.example.com"}
+///     ]
+/// }"#;
+/// let extension: Extension = serde_json::from_str(json_str).unwrap();
+///
+/// let filter = ExtensionFilter {
+///     langs: vec!["es".to_string()],
+///     ..ExtensionFilter::default()
+/// };
+/// let extension = filter.apply(extension).unwrap();
+/// assert_eq!(extension.sources.len(), 1);
+/// assert_eq!(extension.sources[0].name, "ES");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ExtensionFilter {
+    pub langs: Vec<String>,
+    pub nsfw: NsfwFilter,
+    pub pattern: Option<Regex>,
+    pub pkg: Option<GlobMatcher>,
+}
+
+/// What to do with extensions flagged as NSFW in the index.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NsfwFilter {
+    /// Keep NSFW extensions along with the others.
+    #[default]
+    Include,
+    /// Skip NSFW extensions.
+    Exclude,
+    /// Keep only NSFW extensions.
+    Only,
+}
+
+impl ExtensionFilter {
+    /// Returns `extension` with only the sources this filter keeps, or `None`
+    /// if the extension is filtered out or none of its sources is kept. An
+    /// extension without sources is kept only if its own name or package and
+    /// language match.
+    pub fn apply(&self, mut extension: Extension) -> Option<Extension> {
+        let nsfw = extension.nsfw != 0;
+        let keep = match self.nsfw {
+            NsfwFilter::Include => true,
+            NsfwFilter::Exclude => !nsfw,
+            NsfwFilter::Only => nsfw,
+        };
+        if !keep
+            || self
+                .pkg
+                .as_ref()
+                .is_some_and(|pkg| !pkg.is_match(&extension.pkg))
+        {
+            return None;
+        }
+        let name_matches = self.pattern.as_ref().is_none_or(|pattern| {
+            pattern.is_match(&extension.name) || pattern.is_match(&extension.pkg)
+        });
+        let all_langs = self.langs.is_empty()
+            || self.langs.iter().any(|lang| lang == "all")
+            || (extension.lang == "all" && self.langs.iter().any(|lang| lang == "multi"));
+        if extension.sources.is_empty() {
+            // Nothing to narrow down, the extension itself has to match.
+            let lang_matches = all_langs || self.langs.contains(&extension.lang);
+            return (name_matches && lang_matches).then_some(extension);
+        }
+        if let Some(pattern) = self.pattern.as_ref().filter(|_| !name_matches) {
+            extension
+                .sources
+                .retain(|source| pattern.is_match(&source.name) || pattern.is_match(&source.id));
+        }
+        if !all_langs {
+            extension
+                .sources
+                .retain(|source| source.lang == "all" || self.langs.contains(&source.lang));
+        }
+        (!extension.sources.is_empty()).then_some(extension)
+    }
+}
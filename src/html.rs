@@ -0,0 +1,214 @@
+use crate::{report::expiry, CheckStatus, PantherError, ResultRecord};
+use std::{collections::BTreeMap, io::Write};
+
+/// Styles and table sorting embedded in every report, so the page works as a
+/// single file (e.g. on GitHub Pages) without any external asset.
+const HEAD: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Panther report</title>
+<style>
+body { font-family: system-ui, sans-serif; margin: 2rem auto; max-width: 72rem; padding: 0 1rem; color: #222; }
+h1 { margin-bottom: 0.25rem; }
+.summary { color: #555; margin-bottom: 2rem; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 2rem; }
+th, td { text-align: left; padding: 0.35rem 0.6rem; border-bottom: 1px solid #ddd; }
+th { cursor: pointer; user-select: none; background: #f5f5f5; }
+th:hover { background: #e8e8e8; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+.badge { display: inline-block; padding: 0.1rem 0.5rem; border-radius: 0.75rem; font-size: 0.85em; color: #fff; }
+.available { background: #2e7d32; }
+.not_available, .error, .reaped { background: #c62828; }
+.unexpected, .timeout, .expiring { background: #ef6c00; }
+.warning { color: #ef6c00; }
+</style>
+</head>
+<body>
+"#;
+
+const TAIL: &str = r#"<script>
+document.querySelectorAll("table").forEach(function (table) {
+  table.querySelectorAll("th").forEach(function (th, column) {
+    th.addEventListener("click", function () {
+      var body = table.tBodies[0];
+      var ascending = th.dataset.order !== "asc";
+      th.dataset.order = ascending ? "asc" : "desc";
+      var rows = Array.prototype.slice.call(body.rows);
+      rows.sort(function (a, b) {
+        var x = a.cells[column].dataset.value || a.cells[column].textContent;
+        var y = b.cells[column].dataset.value || b.cells[column].textContent;
+        var cmp = (isNaN(x) || isNaN(y) || x === "" || y === "") ? x.localeCompare(y) : x - y;
+        return ascending ? cmp : -cmp;
+      });
+      rows.forEach(function (row) { body.appendChild(row); });
+    });
+  });
+});
+</script>
+</body>
+</html>
+"#;
+
+/// Renders `records` as a self-contained HTML page.
+///
+/// Results are grouped in one section per language, each with a table of
+/// sources that can be sorted by clicking on a column header. Statuses are
+/// shown as colored badges and a summary of the whole run is shown at the
+/// top.
+///
+/// # Errors
+///
+/// * If writing to `writer` fails.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::write_html;
+///
+/// let mut out = Vec::new();
+/// write_html(&mut out, &[]).unwrap();
+/// assert!(String::from_utf8(out).unwrap().starts_with("<!DOCTYPE html>"));
+/// ```
+pub fn write_html<W: Write>(mut writer: W, records: &[ResultRecord]) -> Result<(), PantherError> {
+    let available = records
+        .iter()
+        .filter(|record| record.status.is_available())
+        .count();
+    let mut by_lang: BTreeMap<&str, Vec<&ResultRecord>> = BTreeMap::new();
+    for record in records {
+        by_lang
+            .entry(record.lang.as_deref().unwrap_or("unknown"))
+            .or_default()
+            .push(record);
+    }
+
+    writer.write_all(HEAD.as_bytes())?;
+    writeln!(writer, "<h1>Panther report</h1>")?;
+    writeln!(
+        writer,
+        "<p class=\"summary\">{} sources checked, {} available, {} failing.</p>",
+        records.len(),
+        available,
+        records.len() - available
+    )?;
+    for (lang, records) in by_lang {
+        writeln!(writer, "<h2>{}</h2>", escape(lang))?;
+        writeln!(
+            writer,
+            "<table>\n<thead><tr><th>Extension</th><th>Source</th><th>URL</th>\
+             <th>Status</th><th>HTTP</th><th>Latency (ms)</th><th>Error</th><th>Notes</th></tr></thead>\n<tbody>"
+        )?;
+        for record in records {
+            let status = status_name(record.status);
+            writeln!(
+                writer,
+                "<tr><td>{}</td><td>{}</td><td>{}</td>\
+                 <td data-value=\"{status}\"><span class=\"badge {status}\">{label}</span></td>\
+                 <td class=\"num\">{}</td><td class=\"num\">{}</td><td>{}</td><td>{}</td></tr>",
+                extension(record),
+                escape(record.source.as_deref().unwrap_or("")),
+                url(record),
+                record
+                    .http_status
+                    .map(|s| s.to_string())
+                    .unwrap_or_default(),
+                record.latency_ms.map(|l| l.to_string()).unwrap_or_default(),
+                escape(record.error.as_deref().unwrap_or("")),
+                notes(record),
+                status = status,
+                label = status.replace('_', " "),
+            )?;
+        }
+        writeln!(writer, "</tbody>\n</table>")?;
+    }
+    writer.write_all(TAIL.as_bytes())?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Renders the extension of `record`, followed by its repository when known.
+fn extension(record: &ResultRecord) -> String {
+    let name = escape(record.extension.as_deref().unwrap_or(""));
+    match &record.repo {
+        Some(repo) => format!("{}<br><small>{}</small>", name, escape(repo)),
+        None => name,
+    }
+}
+
+/// Renders the URL of `record` as a link, followed by the IP version it was
+/// checked over, where it moved to if redirects ended on another host, and
+/// warnings about rate limiting and its certificate.
+fn url(record: &ResultRecord) -> String {
+    let mut link = anchor(&record.url, &record.url);
+    if let Some(version) = record.ip_version {
+        link += &format!(" <small>over {}</small>", version);
+    }
+    if let (Some(host), Some(final_url)) = (&record.moved_to, &record.final_url) {
+        link += &format!("<br><small>moved to {}</small>", anchor(final_url, host));
+    }
+    if record.throttled {
+        link += "<br><small class=\"warning\">rate limited</small>";
+    }
+    if record.insecure {
+        link += "<br><small class=\"warning\">certificate not verified</small>";
+    }
+    if let (CheckStatus::Expiring, Some(days_left)) = (record.status, record.cert_days_left) {
+        link += &format!(
+            "<br><small class=\"warning\">certificate {}</small>",
+            expiry(days_left)
+        );
+    }
+    link
+}
+
+/// Renders the annotation of `record`, linking the note when there is a link.
+fn notes(record: &ResultRecord) -> String {
+    match (&record.note, &record.link) {
+        (Some(note), Some(link)) => anchor(link, note),
+        (None, Some(link)) => anchor(link, link),
+        (Some(note), None) => escape(note),
+        (None, None) => String::new(),
+    }
+}
+
+/// Renders `text` as a link to `href`, or as plain text when `href` is not an
+/// http or https URL, so an index can't put a script link in the report.
+fn anchor(href: &str, text: &str) -> String {
+    match reqwest::Url::parse(href) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {
+            format!("<a href=\"{}\">{}</a>", escape(href), escape(text))
+        }
+        _ => escape(text),
+    }
+}
+
+/// Returns the name a status is serialized as, used as its CSS class.
+fn status_name(status: CheckStatus) -> &'static str {
+    match status {
+        CheckStatus::Available => "available",
+        CheckStatus::Expiring => "expiring",
+        CheckStatus::NotAvailable => "not_available",
+        CheckStatus::Unexpected => "unexpected",
+        CheckStatus::Timeout => "timeout",
+        CheckStatus::Reaped => "reaped",
+        CheckStatus::Error => "error",
+    }
+}
+
+/// Escapes the characters that are special in HTML text and attributes.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,436 @@
+use crate::PantherError;
+use futures::{stream, Stream};
+use serde::{
+    de::{SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::{self, File},
+    io::BufReader,
+    path::Path,
+};
+use tokio::sync::mpsc;
+/// The keiyoushi extension index, used when no other index is given.
+pub const DEFAULT_INDEX_URL: &str =
+    "https://raw.githubusercontent.com/keiyoushi/extensions/refs/heads/repo/index.min.json";
+/// The root of the keiyoushi extension repository, holding the index and the
+/// `apk/` directory.
+pub const DEFAULT_REPO_URL: &str =
+    "https://raw.githubusercontent.com/keiyoushi/extensions/refs/heads/repo";
+/// Represents a data source with its associated metadata.
+///
+/// This struct holds information about a specific source, including its name,
+/// language, unique identifier, and base URL. It's designed to be deserialized
+/// from a JSON format, where the `baseUrl` field is renamed to `base_url`
+/// during deserialization.
+///
+/// # Fields
+///
+/// * `name`: The human-readable name of the source.
+/// * `lang`: The language associated with the source (e.g., "en", "es").
+/// * `id`: A unique identifier for the source.
+/// * `base_url`: The base URL for accessing data from this source. Note that
+///   in the JSON representation, this field is named `baseUrl`.
+/// * `version_id`: The version of the source's identifier (`versionId`), only
+///   present in the full `index.json`.
+/// * `extra`: Any other field of the source, kept as raw JSON so metadata
+///   added to the index later is neither lost nor a parsing error.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::Source;
+///
+/// fn main() {
+///     let json_str = r#"{
+///         "name": "My Source",
+///         "lang": "en",
+///         "id": "source123",
+///         "baseUrl": "https://example.com",
+///         "versionId": 1,
+///         "isNew": true
+///     }"#;
+///
+///     let source: Source = serde_json::from_str(json_str).unwrap();
+///
+///     println!("{:?}", source);
+///     assert_eq!(source.name, "My Source");
+///     assert_eq!(source.lang, "en");
+///     assert_eq!(source.id, "source123");
+///     assert_eq!(source.base_url, "https://example.com");
+///     assert_eq!(source.version_id, Some(1));
+///     assert_eq!(source.extra["isNew"], true);
+/// }
+/// ```
+#[derive(Deserialize, Debug)]
+pub struct Source {
+    pub name: String,
+    pub lang: String,
+    pub id: String,
+    #[serde(rename = "baseUrl")]
+    pub base_url: String,
+    #[serde(default, rename = "versionId")]
+    pub version_id: Option<i32>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// Represents an extension with its associated metadata and data sources.
+///
+/// This struct holds information about an extension, including its name,
+/// package name, APK file name, language, code, version, NSFW rating, and
+/// a list of data sources. It's designed to be deserialized from a JSON
+/// format.
+///
+/// # Fields
+///
+/// * `name`: The human-readable name of the extension.
+/// * `pkg`: The package name of the extension.
+/// * `apk`: The APK file name of the extension.
+/// * `lang`: The language associated with the extension (e.g., "en", "es").
+/// * `code`: A numerical code associated with the extension.
+/// * `version`: The version string of the extension.
+/// * `nsfw`: A numerical rating indicating the NSFW (Not Safe For Work) level.
+/// * `sources`: A vector of `Source` structs, representing the data sources
+///   provided by the extension. Empty when the index leaves it out.
+/// * `icon`: The URL of the extension icon, if the index provides one.
+/// * `has_update`: Whether an update is pending (`hasUpdate`).
+/// * `has_readme`: Whether the extension has a README (`hasReadme`).
+/// * `has_changelog`: Whether the extension has a changelog (`hasChangelog`).
+///
+/// The last four are only found in the full `index.json`, and are `None` when
+/// reading `index.min.json`. Their flags are accepted both as booleans and as
+/// `0`/`1`. Unknown fields are ignored.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::Extension;
+///
+/// fn main() {
+///     let json_str = r#"{
+///         "name": "My Extension",
+///         "pkg": "com.example.extension",
+///         "apk": "extension.apk",
+///         "lang": "en",
+///         "code": 123,
+///         "version": "1.0.0",
+///         "nsfw": 0,
+///         "hasReadme": 1,
+///         "hasChangelog": false,
+///         "sources": [
+///             {
+///                 "name": "Source 1",
+///                 "lang": "en",
+///                 "id": "source1",
+///                 "baseUrl": "https://www.google.com"
+///             },
+///             {
+///                 "name": "Source 2",
+///                 "lang": "es",
+///                 "id": "source2",
+///                 "baseUrl": "https://www.google.com/"
+///             }
+///         ]
+///     }"#;
+///
+///     let extension: Extension = serde_json::from_str(json_str).unwrap();
+///
+///     println!("{:?}", extension);
+///     assert_eq!(extension.name, "My Extension");
+///     assert_eq!(extension.sources.len(), 2);
+///     assert_eq!(extension.sources[0].name, "Source 1");
+///     assert_eq!(extension.has_readme, Some(true));
+///     assert_eq!(extension.has_update, None);
+/// }
+/// ```
+#[derive(Deserialize, Debug)]
+pub struct Extension {
+    pub name: String,
+    pub pkg: String,
+    pub apk: String,
+    pub lang: String,
+    pub code: i32,
+    pub version: String,
+    pub nsfw: i32,
+    #[serde(default)]
+    pub sources: Vec<Source>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default, rename = "hasUpdate", deserialize_with = "flag")]
+    pub has_update: Option<bool>,
+    #[serde(default, rename = "hasReadme", deserialize_with = "flag")]
+    pub has_readme: Option<bool>,
+    #[serde(default, rename = "hasChangelog", deserialize_with = "flag")]
+    pub has_changelog: Option<bool>,
+}
+
+/// Deserializes an optional flag written either as a boolean or as `0`/`1`.
+fn flag<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<bool>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Flag {
+        Bool(bool),
+        Int(i64),
+    }
+    Ok(
+        Option::<Flag>::deserialize(deserializer)?.map(|flag| match flag {
+            Flag::Bool(value) => value,
+            Flag::Int(value) => value != 0,
+        }),
+    )
+}
+/// Downloads a JSON file from a GitHub URL and saves it to a specified output path.
+///
+/// This asynchronous function fetches data from the given URL, assuming it's a JSON file,
+/// and writes the downloaded content to a file at the provided output path.
+///
+/// # Arguments
+///
+/// * `url`: A string slice representing the GitHub URL of the JSON file to download.
+/// * `output_path`: The path where the downloaded file should be saved. Like
+///   `read_json_from_file`, it accepts any path-like type.
+///
+/// # Returns
+///
+/// * `Result<(), PantherError>`: Returns `Ok(())` if the download and save were successful,
+///   or a `PantherError` describing which part of the process failed.
+///
+/// # Errors
+///
+/// This function can return errors in the following scenarios:
+///
+/// * If the HTTP request fails (e.g., invalid URL, network issues).
+/// * If creating the output file fails (e.g., permission issues, invalid path).
+/// * If reading the response body fails.
+/// * If writing to the file fails.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use panther::download_json_github;
+/// use tokio;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let url = "https://api.github.com/repos/octocat/Spoon-Knife/contents/file.json";
+///     let output_path = "downloaded_file.json";
+///
+///     download_json_github(url, output_path).await?;
+///
+///     // Now, 'downloaded_file.json' contains the JSON data.
+///
+///     Ok(())
+/// }
+/// ```
+pub async fn download_json_github<P: AsRef<Path>>(
+    url: &str,
+    output_path: P,
+) -> Result<(), PantherError> {
+    download_json_with_client(&reqwest::Client::new(), url, output_path).await
+}
+/// Like [`download_json_github`], sending the request with `client`, e.g. one
+/// configured with a proxy.
+pub async fn download_json_with_client<P: AsRef<Path>>(
+    client: &reqwest::Client,
+    url: &str,
+    output_path: P,
+) -> Result<(), PantherError> {
+    let mut response = client.get(url).send().await?;
+    if response.status().is_success() {
+        let mut file = fs::File::create(output_path)?;
+        /* Reading and writing in chunks avoids creating a large buffer
+        for reading the whole response body which is 432Kb today's 30/03/2025. */
+        while let Some(chunk) = response.chunk().await? {
+            std::io::copy(&mut chunk.as_ref(), &mut file)?;
+        }
+        Ok(())
+    } else if response.status() == reqwest::StatusCode::NOT_FOUND {
+        Err(PantherError::NotFound)
+    } else {
+        Err(PantherError::Http(response.status()))
+    }
+}
+/// Downloads an index and parses it in memory, without touching the disk.
+///
+/// This is the counterpart of `download_json_github` followed by
+/// `read_json_from_file` for environments where nothing may be written. The
+/// whole index is held in memory, so prefer the file-based functions when
+/// memory is tight.
+///
+/// # Arguments
+///
+/// * `url`: A string slice representing the URL of the index to download.
+///
+/// # Returns
+///
+/// * `Result<Vec<Extension>, PantherError>`: The extensions of the index, or a
+///   `PantherError` describing whether the download or the parsing failed.
+///
+/// # Errors
+///
+/// * If the HTTP request fails or the server answers with a non-success status.
+/// * If the response body is not a valid index.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use panther::{fetch_json_github, DEFAULT_INDEX_URL};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), panther::PantherError> {
+///     let extensions = fetch_json_github(DEFAULT_INDEX_URL).await?;
+///     println!("{} extensions", extensions.len());
+///     Ok(())
+/// }
+/// ```
+pub async fn fetch_json_github(url: &str) -> Result<Vec<Extension>, PantherError> {
+    fetch_json_with_client(&reqwest::Client::new(), url).await
+}
+/// Like [`fetch_json_github`], sending the request with `client`, e.g. one
+/// configured with a proxy.
+pub async fn fetch_json_with_client(
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<Vec<Extension>, PantherError> {
+    let response = client.get(url).send().await?;
+    if response.status().is_success() {
+        Ok(serde_json::from_slice(&response.bytes().await?)?)
+    } else if response.status() == reqwest::StatusCode::NOT_FOUND {
+        Err(PantherError::NotFound)
+    } else {
+        Err(PantherError::Http(response.status()))
+    }
+}
+/// Reads a JSON file and deserializes its contents into a vector of `Extension` structs.
+///
+/// This function opens the file specified by the given path, reads its JSON contents,
+/// and deserializes it into a `Vec<Extension>`. It uses buffered reading for efficiency.
+///
+/// # Type Parameters
+///
+/// * `P`: A type that implements `AsRef<Path>`, representing the file path. This allows
+///   the function to accept various path-like types (e.g., `&str`, `String`, `Path`).
+///
+/// # Arguments
+///
+/// * `path`: The path to the JSON file to read.
+///
+/// # Returns
+///
+/// * `Result<Vec<Extension>, PantherError>`: Returns a `Result` containing a vector
+///   of `Extension` structs if the file is successfully read and deserialized, or
+///   a `PantherError::Io` or `PantherError::Json` if any part of the process fails.
+///
+/// # Errors
+///
+/// This function can return errors in the following scenarios:
+///
+/// * If the file cannot be opened (e.g., file not found, permission issues).
+/// * If reading the file fails.
+/// * If deserializing the JSON content fails (e.g., invalid JSON format, mismatching types).
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::path::Path;
+/// use panther::{read_json_from_file, Extension};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     // Assuming you have a file named "extensions.json" in the same directory.
+///     let path = Path::new("extensions.json");
+///     let extensions: Vec<Extension> = read_json_from_file(path)?;
+///
+///     println!("Read {} extensions from file.", extensions.len());
+///     // You can now work with the 'extensions' vector.
+///     Ok(())
+/// }
+/// ```
+pub fn read_json_from_file<P: AsRef<Path>>(path: P) -> Result<Vec<Extension>, PantherError> {
+    // Open the file in read-only mode with buffer.
+    let file = File::open(path)?;
+    let reader = BufReader::new(file);
+
+    // Read the JSON contents of the file as an instance of `Extension`.
+    let json = serde_json::from_reader(reader)?;
+
+    // Return the `Extension`.
+    Ok(json)
+}
+/// Reads a JSON index file one extension at a time.
+///
+/// Unlike `read_json_from_file`, the index is never held in memory as a
+/// whole: a blocking task parses the file and hands extensions over a small
+/// bounded channel, so parsing only runs ahead of the consumer by a few dozen
+/// extensions. This keeps memory flat no matter how large the index is.
+///
+/// # Arguments
+///
+/// * `path`: The path to the JSON file to read.
+///
+/// # Returns
+///
+/// * `Result<impl Stream<Item = Result<Extension, PantherError>>, PantherError>`:
+///   A stream of extensions in file order. A parse error is yielded as the
+///   last item of the stream.
+///
+/// # Errors
+///
+/// * If the file cannot be opened (e.g., file not found, permission issues).
+///
+/// Reading and deserialization errors are yielded by the stream instead.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use futures::StreamExt;
+/// use panther::stream_json_from_file;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut extensions = Box::pin(stream_json_from_file("index.min.json")?);
+///     while let Some(extension) = extensions.next().await {
+///         println!("{}", extension?.name);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn stream_json_from_file<P: AsRef<Path>>(
+    path: P,
+) -> Result<impl Stream<Item = Result<Extension, PantherError>>, PantherError> {
+    let file = File::open(path)?;
+    let (tx, rx) = mpsc::channel(64);
+    tokio::task::spawn_blocking(move || {
+        let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(file));
+        if let Err(e) = deserializer.deserialize_seq(ExtensionSender(&tx)) {
+            // Nobody to tell if the receiver is gone already.
+            let _ = tx.blocking_send(Err(e.into()));
+        }
+    });
+    Ok(stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|item| (item, rx))
+    }))
+}
+/// Sends every element of a JSON array of extensions down a channel as soon
+/// as it is parsed, instead of collecting them.
+struct ExtensionSender<'a>(&'a mpsc::Sender<Result<Extension, PantherError>>);
+
+impl<'de> Visitor<'de> for ExtensionSender<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of extensions")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(extension) = seq.next_element()? {
+            if self.0.blocking_send(Ok(extension)).is_err() {
+                // The consumer stopped listening, no point in parsing the rest.
+                break;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use std::{collections::HashMap, time::Duration};
+
+/// Latency statistics over the checks of a run, to spot sources that answer
+/// but are too slow to be usable.
+///
+/// # Fields
+///
+/// * `count`: How many latencies were measured.
+/// * `min`, `median`, `p95`, `max`: The distribution of latencies, with
+///   percentiles taken by nearest rank.
+/// * `slowest`: The slowest hosts with their mean latency, slowest first.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::LatencySummary;
+/// use std::time::Duration;
+///
+/// let samples = (1..=20).map(|i| {
+///     let host = if i > 18 { "slow.example.com" } else { "example.com" };
+///     (format!("https://{}/{}", host, i), Duration::from_millis(i * 100))
+/// });
+/// let summary = LatencySummary::new(samples, 1).unwrap();
+/// assert_eq!(summary.count, 20);
+/// assert_eq!(summary.median, Duration::from_millis(1000));
+/// assert_eq!(summary.p95, Duration::from_millis(1900));
+/// assert_eq!(summary.slowest[0].0, "slow.example.com");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencySummary {
+    pub count: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+    pub max: Duration,
+    pub slowest: Vec<(String, Duration)>,
+}
+
+impl LatencySummary {
+    /// Summarizes `samples`, pairs of a checked URL and its latency, keeping
+    /// the `slowest` slowest hosts. URLs that do not parse count as their own
+    /// host. Returns `None` if there are no samples.
+    pub fn new<I>(samples: I, slowest: usize) -> Option<Self>
+    where
+        I: IntoIterator<Item = (String, Duration)>,
+    {
+        let mut latencies = Vec::new();
+        let mut hosts: HashMap<String, (Duration, u32)> = HashMap::new();
+        for (url, latency) in samples {
+            let host = reqwest::Url::parse(&url)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_string))
+                .unwrap_or(url);
+            let (sum, count) = hosts.entry(host).or_default();
+            *sum += latency;
+            *count += 1;
+            latencies.push(latency);
+        }
+        if latencies.is_empty() {
+            return None;
+        }
+        latencies.sort_unstable();
+        let rank = |p: f64| {
+            let rank = (p * latencies.len() as f64).ceil() as usize;
+            latencies[rank.clamp(1, latencies.len()) - 1]
+        };
+        let mut hosts: Vec<(String, Duration)> = hosts
+            .into_iter()
+            .map(|(host, (sum, count))| (host, sum / count))
+            .collect();
+        hosts.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        hosts.truncate(slowest);
+        Some(LatencySummary {
+            count: latencies.len(),
+            min: latencies[0],
+            median: rank(0.5),
+            p95: rank(0.95),
+            max: latencies[latencies.len() - 1],
+            slowest: hosts,
+        })
+    }
+}
@@ -0,0 +1,61 @@
+//! Panther bulk-validates manga source URLs from a Tachiyomi extension
+//! index.
+//!
+//! The library exposes the index model ([`Extension`], [`Source`]), helpers
+//! to download and read an index, and [`check_url`] to check a single source.
+//! The `panther` binary is a thin wrapper around these.
+mod audit;
+mod certificate;
+#[cfg(feature = "chaos")]
+mod chaos;
+mod check;
+mod coverage;
+mod diff;
+mod dns;
+mod error;
+mod export;
+mod filter;
+mod html;
+mod index;
+mod latency;
+mod limit;
+mod mirror;
+mod policy;
+mod report;
+mod retry;
+mod store;
+mod timing;
+mod validate;
+
+pub use audit::{audit_repo, AuditFinding};
+pub use certificate::CertificateInfo;
+pub use check::{
+    check_url, join_path, Capture, CheckOptions, CheckResult, CheckStatus, Checker, IpVersion,
+    Target, PROBE_PATHS,
+};
+pub use coverage::{field_coverage, FieldCoverage};
+pub use diff::{diff_indexes, IndexChange};
+pub use dns::DnsServer;
+pub use error::PantherError;
+pub use export::{write_gatus, write_uptime_kuma};
+pub use filter::{ExtensionFilter, NsfwFilter};
+pub use html::write_html;
+pub use index::{
+    download_json_github, download_json_with_client, fetch_json_github, fetch_json_with_client,
+    read_json_from_file, stream_json_from_file, Extension, Source, DEFAULT_INDEX_URL,
+    DEFAULT_REPO_URL,
+};
+pub use latency::LatencySummary;
+pub use limit::HostLimit;
+pub use mirror::{
+    verify_mirror, verify_mirror_with_client, FileComparison, MirrorReport, MirrorStatus,
+};
+pub use policy::{AcceptPolicy, StatusSet};
+pub use report::{
+    annotate, expiry, read_annotations, read_records, write_csv, write_json, write_markdown,
+    write_ndjson, Annotation, ResultRecord,
+};
+pub use retry::{parse_retry_after, RetryPolicy};
+pub use store::{MemoryStore, ResultStore, StoredResult};
+pub use timing::{trace_timing, Timing};
+pub use validate::{validate_index, Violation};
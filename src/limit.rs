@@ -0,0 +1,121 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    sync::{Mutex as AsyncMutex, OwnedSemaphorePermit, Semaphore},
+    time::Instant,
+};
+
+/// How hard a single host may be hit during a run.
+///
+/// Many sources of an index share a domain, and checking them all at once
+/// gets panther rate limited, skewing the results. Requests to a host past
+/// its limit wait for their turn. The limit applies under the global
+/// [`crate::CheckOptions::concurrency`]: a check waiting for its host still
+/// takes up one of its slots.
+///
+/// # Fields
+///
+/// * `max_in_flight`: How many requests to the same host may run at once.
+///   `0`, the default, does not limit them.
+/// * `min_delay`: The shortest time between the starts of two requests to
+///   the same host. Zero by default.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::{CheckOptions, HostLimit};
+/// use std::time::Duration;
+///
+/// let options = CheckOptions {
+///     host_limit: HostLimit {
+///         max_in_flight: 2,
+///         min_delay: Duration::from_millis(250),
+///     },
+///     ..CheckOptions::default()
+/// };
+/// assert!(!options.host_limit.is_unlimited());
+/// assert!(HostLimit::default().is_unlimited());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HostLimit {
+    pub max_in_flight: usize,
+    pub min_delay: Duration,
+}
+
+impl HostLimit {
+    /// Returns whether requests to a host are let through without waiting.
+    pub fn is_unlimited(&self) -> bool {
+        self.max_in_flight == 0 && self.min_delay.is_zero()
+    }
+}
+
+/// Enforces a [`HostLimit`] on the requests of a [`crate::Checker`].
+#[derive(Debug)]
+pub(crate) struct HostLimiter {
+    limit: HostLimit,
+    hosts: Mutex<HashMap<String, Arc<Host>>>,
+}
+
+/// The state of a single host.
+#[derive(Debug)]
+struct Host {
+    permits: Option<Arc<Semaphore>>,
+    /// When the next request may start.
+    next: AsyncMutex<Instant>,
+}
+
+impl HostLimiter {
+    pub(crate) fn new(limit: HostLimit) -> Self {
+        HostLimiter {
+            limit,
+            hosts: Mutex::default(),
+        }
+    }
+
+    /// Waits until a request to the host of `url` may start. The request
+    /// counts as in flight until the returned permit is dropped.
+    pub(crate) async fn acquire(&self, url: &str) -> Option<OwnedSemaphorePermit> {
+        if self.limit.is_unlimited() {
+            return None;
+        }
+        let host = reqwest::Url::parse(url)
+            .ok()?
+            .host_str()?
+            .to_ascii_lowercase();
+        let host = self
+            .hosts
+            .lock()
+            .expect("hosts lock")
+            .entry(host)
+            .or_insert_with(|| {
+                Arc::new(Host {
+                    permits: (self.limit.max_in_flight > 0)
+                        .then(|| Arc::new(Semaphore::new(self.limit.max_in_flight))),
+                    next: AsyncMutex::new(Instant::now()),
+                })
+            })
+            .clone();
+        let permit = match &host.permits {
+            Some(permits) => Some(permits.clone().acquire_owned().await.ok()?),
+            None => None,
+        };
+        if !self.limit.min_delay.is_zero() {
+            // Holding the lock while sleeping lines the waiting requests up.
+            let mut next = host.next.lock().await;
+            tokio::time::sleep_until(*next).await;
+            *next = Instant::now()
+                .checked_add(self.limit.min_delay)
+                .unwrap_or_else(far_future);
+        }
+        permit
+    }
+}
+
+/// An instant no run lives to see, standing in for delays too long to add to
+/// the current one.
+fn far_future() -> Instant {
+    Instant::now() + Duration::from_secs(30 * 365 * 24 * 60 * 60)
+}
@@ -1,366 +1,606 @@
-use colored::Colorize;
-use reqwest::StatusCode;
-use serde::Deserialize;
+mod cli;
+mod config;
+mod output;
+
+use clap::Parser;
+use cli::{Cli, Command, ExportFormat, Format, IndexArgs, ProgressFormat};
+use config::Config;
+use futures::{future, stream, Stream, StreamExt};
+use output::{
+    advance, print_change, print_comparison, print_coverage, print_error, print_extension,
+    print_finding, print_latency, print_mirror, print_result, print_saved, print_source,
+    print_violations, print_warning, progress_bar, suspended, Progress, Verbosity,
+};
+use panther::{
+    annotate, audit_repo, diff_indexes, download_json_with_client, fetch_json_with_client,
+    field_coverage, join_path, read_annotations, read_json_from_file, read_records,
+    stream_json_from_file, validate_index, verify_mirror_with_client, write_csv, write_gatus,
+    write_html, write_json, write_markdown, write_ndjson, write_uptime_kuma, CheckOptions,
+    CheckResult, Checker, Extension, LatencySummary, PantherError, ResultRecord, Target,
+};
+use sha2::{Digest, Sha256};
 use std::{
-    error::Error,
-    fs::{self, File},
-    io::BufReader,
+    cell::RefCell,
+    collections::{BTreeMap, HashSet},
+    io::{IsTerminal, Write},
     path::Path,
+    pin::pin,
+    process::ExitCode,
+    time::{SystemTime, UNIX_EPOCH},
 };
 /*
  * TODO:
  * [x] Improve error handling in function test_url, may fail if dns cannot resolve domain.
  * [x] Pretiffy terminal prints in test_url
  * [x] More options for different HTTP codes
- * [ ] Add command line args to test single domain or list
+ * [x] Add command line args to test single domain or list
  * [x] Check if response was okay in download_json_github
  */
-/// Represents a data source with its associated metadata.
-///
-/// This struct holds information about a specific source, including its name,
-/// language, unique identifier, and base URL. It's designed to be deserialized
-/// from a JSON format, where the `baseUrl` field is renamed to `base_url`
-/// during deserialization.
-///
-/// # Fields
-///
-/// * `name`: The human-readable name of the source.
-/// * `lang`: The language associated with the source (e.g., "en", "es").
-/// * `id`: A unique identifier for the source.
-/// * `base_url`: The base URL for accessing data from this source. Note that
-///   in the JSON representation, this field is named `baseUrl`.
-///
-/// # Example
-///
-/// ```rust
-/// use serde::Deserialize;
-///
-/// #[derive(Deserialize, Debug)]
-/// struct Source {
-///     name: String,
-///     lang: String,
-///     id: String,
-///     #[serde(rename = "baseUrl")]
-///     base_url: String,
-/// }
-///
-/// fn main() {
-///     let json_str = r#"{
-///         "name": "My Source",
-///         "lang": "en",
-///         "id": "source123",
-///         "baseUrl": "https://example.com"
-///     }"#;
-///
-///     let source: Source = serde_json::from_str(json_str).unwrap();
-///
-///     println!("{:?}", source);
-///     assert_eq!(source.name, "My Source");
-///     assert_eq!(source.lang, "en");
-///     assert_eq!(source.id, "source123");
-///     assert_eq!(source.base_url, "https://example.com");
-/// }
-/// ```
-#[derive(Deserialize, Debug)]
-#[allow(unused)]
-struct Source {
-    name: String,
-    lang: String,
-    id: String,
-    #[serde(rename = "baseUrl")]
-    base_url: String,
-}
-
-/// Represents an extension with its associated metadata and data sources.
-///
-/// This struct holds information about an extension, including its name,
-/// package name, APK file name, language, code, version, NSFW rating, and
-/// a list of data sources. It's designed to be deserialized from a JSON
-/// format.
-///
-/// # Fields
-///
-/// * `name`: The human-readable name of the extension.
-/// * `pkg`: The package name of the extension.
-/// * `apk`: The APK file name of the extension.
-/// * `lang`: The language associated with the extension (e.g., "en", "es").
-/// * `code`: A numerical code associated with the extension.
-/// * `version`: The version string of the extension.
-/// * `nsfw`: A numerical rating indicating the NSFW (Not Safe For Work) level.
-/// * `sources`: A vector of `Source` structs, representing the data sources
-///     provided by the extension.
-///
-/// # Example
-///
-/// ```rust
-/// use serde::Deserialize;
-///
-/// #[derive(Deserialize, Debug)]
-/// struct Source {
-///     name: String,
-///     lang: String,
-///     id: String,
-///     #[serde(rename = "baseUrl")]
-///     base_url: String,
-/// }
-///
-/// #[derive(Deserialize, Debug)]
-/// struct Extension {
-///     name: String,
-///     pkg: String,
-///     apk: String,
-///     lang: String,
-///     code: i32,
-///     version: String,
-///     nsfw: i32,
-///     sources: Vec<Source>,
-/// }
-///
-/// fn main() {
-///     let json_str = r#"{
-///         "name": "My Extension",
-///         "pkg": "com.example.extension",
-///         "apk": "extension.apk",
-///         "lang": "en",
-///         "code": 123,
-///         "version": "1.0.0",
-///         "nsfw": 0,
-///         "sources": [
-///             {
-///                 "name": "Source 1",
-///                 "lang": "en",
-///                 "id": "source1",
-///                 "baseUrl": "https://www.google.com"
-///             },
-///             {
-///                 "name": "Source 2",
-///                 "lang": "es",
-///                 "id": "source2",
-///                 "baseUrl": "https://www.google.com/"
-///             }
-///         ]
-///     }"#;
-///
-///     let extension: Extension = serde_json::from_str(json_str).unwrap();
-///
-///     println!("{:?}", extension);
-///     assert_eq!(extension.name, "My Extension");
-///     assert_eq!(extension.sources.len(), 2);
-///     assert_eq!(extension.sources[0].name, "Source 1");
-/// }
-/// ```
-#[derive(Deserialize, Debug)]
-#[allow(unused)]
-struct Extension {
-    name: String,
-    pkg: String,
-    apk: String,
-    lang: String,
-    code: i32,
-    version: String,
-    nsfw: i32,
-    sources: Vec<Source>,
+/// Streams the indexes selected by `args`, downloading them first unless a
+/// local file was given, and keeps only the extensions and sources matching
+/// the filters. Each extension comes with the `--repo` it was found in, if
+/// any. A single downloaded index is saved to `--output`; with `read_only` or
+/// several repositories, indexes are kept in memory instead. Of several
+/// repositories, those that cannot be fetched are warned about and skipped,
+/// setting `passed` to false.
+async fn load_index<'a>(
+    args: &'a IndexArgs,
+    client: &reqwest::Client,
+    read_only: bool,
+    verbosity: Verbosity,
+    passed: &mut bool,
+) -> Result<impl Stream<Item = Result<(Option<String>, Extension), PantherError>> + 'a, PantherError>
+{
+    let indexes: Vec<(Option<String>, String)> = if args.repo.is_empty() {
+        vec![(None, args.index_url.clone())]
+    } else {
+        args.repo
+            .iter()
+            .map(|repo| {
+                let url = format!("{}/index.min.json", repo.trim_end_matches('/'));
+                (Some(repo.clone()), url)
+            })
+            .collect()
+    };
+    let extensions = match &args.index {
+        Some(path) => stream_json_from_file(path)?
+            .map(|item| item.map(|extension| (None, extension)))
+            .boxed_local(),
+        None if read_only || indexes.len() > 1 => {
+            let several = indexes.len() > 1;
+            let mut extensions = Vec::new();
+            for (repo, url) in indexes {
+                match fetch_json_with_client(client, &url).await {
+                    Ok(index) => {
+                        extensions.extend(index.into_iter().map(|e| Ok((repo.clone(), e))));
+                    }
+                    // One unreachable repository should not hide the others.
+                    Err(e) if several => {
+                        print_warning(&format!("skipping {}: {}", url, e));
+                        *passed = false;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            stream::iter(extensions).boxed_local()
+        }
+        None => {
+            let (repo, url) = indexes.into_iter().next().expect("one index at least");
+            download_json_with_client(client, &url, &args.output).await?;
+            print_saved("File downloaded successfully to:", &args.output, verbosity);
+            stream_json_from_file(&args.output)?
+                .map(move |item| item.map(|extension| (repo.clone(), extension)))
+                .boxed_local()
+        }
+    };
+    let filter = args.filter();
+    Ok(extensions.filter_map(move |item| {
+        future::ready(match item {
+            Ok((repo, extension)) => filter.apply(extension).map(|e| Ok((repo, e))),
+            Err(e) => Some(Err(e)),
+        })
+    }))
 }
-/// Downloads a JSON file from a GitHub URL and saves it to a specified output path.
-///
-/// This asynchronous function fetches data from the given URL, assuming it's a JSON file,
-/// and writes the downloaded content to a file at the provided output path.
-///
-/// # Arguments
-///
-/// * `url`: A string slice representing the GitHub URL of the JSON file to download.
-/// * `output_path`: A string slice representing the path where the downloaded file should be saved.
-///
-/// # Returns
-///
-/// * `Result<(), Box<dyn std::error::Error>>`: Returns `Ok(())` if the download and save were successful,
-///   or an error wrapped in a `Box<dyn std::error::Error>` if any part of the process fails.
-///
-/// # Errors
-///
-/// This function can return errors in the following scenarios:
-///
-/// * If the HTTP request fails (e.g., invalid URL, network issues).
-/// * If creating the output file fails (e.g., permission issues, invalid path).
-/// * If reading the response body fails.
-/// * If writing to the file fails.
-///
-/// # Example
-///
-/// ```rust,no_run
-/// use panther::download_json_github;
-/// use tokio;
-///
-/// #[tokio::main]
-/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-///     let url = "https://api.github.com/repos/octocat/Spoon-Knife/contents/file.json";
-///     let output_path = "downloaded_file.json";
-///
-///     download_json_github(url, output_path).await?;
-///
-///     // Now, 'downloaded_file.json' contains the JSON data.
-///
-///     Ok(())
-/// }
-/// ```
-async fn download_json_github(
-    url: &str,
-    output_path: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let mut response = reqwest::get(url).await?;
-    if response.status().is_success() {
-        let mut file = fs::File::create(output_path)?;
-        /* Reading and writing in chunks avoids creating a large buffer
-        for reading the whole response body which is 432Kb today's 30/03/2025. */
-        while let Some(chunk) = response.chunk().await? {
-            std::io::copy(&mut chunk.as_ref(), &mut file)?;
+/// How much of the URL of a check goes into the name of its capture file,
+/// well under the file name length limits.
+const CAPTURE_NAME_CHARS: usize = 64;
+/// Saves what is known about the failed check `result` to a new file in
+/// `dir`: the request, the status or error, and the captured headers and
+/// body if any. Files are never overwritten; a counter is added to the name
+/// of captures that would share one.
+fn save_capture(dir: &Path, result: &CheckResult) -> std::io::Result<()> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    // The start of the URL keeps names readable, and a hash of all of it
+    // keeps long URLs sharing that start apart.
+    let readable: String = result
+        .url
+        .chars()
+        .take(CAPTURE_NAME_CHARS)
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let hash = format!("{:x}", Sha256::digest(result.url.as_bytes()));
+    let mut name = format!("{}-{}", readable, &hash[..12]);
+    if let Some(version) = result.ip_version {
+        name += &format!("-{}", version);
+    }
+    let mut counter = 0;
+    let file = loop {
+        let suffix = match counter {
+            0 => String::new(),
+            n => format!("-{}", n),
+        };
+        let path = dir.join(format!("{}-{}{}.txt", now.as_millis(), name, suffix));
+        match std::fs::File::create_new(path) {
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => counter += 1,
+            file => break file?,
         }
-        Ok(())
-    } else if response.status() == reqwest::StatusCode::NOT_FOUND {
-        return Err("Resource not found".into());
-    } else {
-        return Err(format!("HTTP error: {}", response.status()).into());
+    };
+    let mut file = std::io::BufWriter::new(file);
+    let url = join_path(&result.url, result.path.as_deref().unwrap_or("/"));
+    match &result.method {
+        Some(method) => writeln!(file, "{} {}", method, url)?,
+        // No request was sent, e.g. the deadline passed first.
+        None => writeln!(file, "{}", url)?,
+    }
+    match (&result.http_status, &result.error) {
+        (Some(status), _) => writeln!(file, "{}", status)?,
+        (None, Some(e)) => writeln!(file, "{}", e)?,
+        (None, None) => {}
+    }
+    if let Some(capture) = &result.capture {
+        for (name, value) in &capture.headers {
+            writeln!(
+                file,
+                "{}: {}",
+                name,
+                String::from_utf8_lossy(value.as_bytes())
+            )?;
+        }
+        writeln!(file)?;
+        file.write_all(&capture.body)?;
     }
+    file.flush()
 }
-/// Reads a JSON file and deserializes its contents into a vector of `Extension` structs.
-///
-/// This function opens the file specified by the given path, reads its JSON contents,
-/// and deserializes it into a `Vec<Extension>`. It uses buffered reading for efficiency.
-///
-/// # Type Parameters
-///
-/// * `P`: A type that implements `AsRef<Path>`, representing the file path. This allows
-///   the function to accept various path-like types (e.g., `&str`, `String`, `Path`).
-///
-/// # Arguments
-///
-/// * `path`: The path to the JSON file to read.
-///
-/// # Returns
-///
-/// * `Result<Vec<Extension>, Box<dyn Error>>`: Returns a `Result` containing a vector
-///   of `Extension` structs if the file is successfully read and deserialized, or
-///   an error if any part of the process fails.
-///
-/// # Errors
-///
-/// This function can return errors in the following scenarios:
-///
-/// * If the file cannot be opened (e.g., file not found, permission issues).
-/// * If reading the file fails.
-/// * If deserializing the JSON content fails (e.g., invalid JSON format, mismatching types).
-///
-/// # Example
-///
-/// ```rust,no_run
-/// use std::path::Path;
-/// use panther::{read_json_from_file, Extension, Source};
-///
-/// fn main() -> Result<(), Box<dyn std::error::Error>> {
-///     // Assuming you have a file named "extensions.json" in the same directory.
-///     let path = Path::new("extensions.json");
-///     let extensions: Vec<Extension> = read_json_from_file(path)?;
-///
-///     println!("Read {} extensions from file.", extensions.len());
-///     // You can now work with the 'extensions' vector.
-///     Ok(())
-/// }
-/// ```
-fn read_json_from_file<P: AsRef<Path>>(path: P) -> Result<Vec<Extension>, Box<dyn Error>> {
-    // Open the file in read-only mode with buffer.
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-
-    // Read the JSON contents of the file as an instance of `Extension`.
-    let json = serde_json::from_reader(reader)?;
-
-    // Return the `Extension`.
-    Ok(json)
+/// Returns the resident memory of this process in bytes, where the platform
+/// exposes it.
+fn resident_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
 }
-/// Tests the availability of a given URL by sending an HTTP GET request.
-///
-/// This asynchronous function sends a GET request to the provided URL and
-/// prints the HTTP status code to the console. If the status code is `200 OK`,
-/// it indicates that the URL is available. Otherwise, it prints the URL and
-/// the received status code.
-///
-/// # Arguments
-///
-/// * `url`: A string slice representing the URL to test.
-///
-/// # Returns
-///
-/// * `Result<(), Box<dyn std::error::Error>>`: Returns `Ok(())` if the request
-///   was successful (regardless of the status code), or an error wrapped in
-///   a `Box<dyn std::error::Error>` if the request failed.
-///
-/// # Errors
-///
-/// This function can return errors in the following scenarios:
-///
-/// * If the HTTP request fails (e.g., invalid URL, network issues).
-///
-/// # Example
-///
-/// ```rust,no_run
-/// use panther::test_url;
-/// use tokio;
-///
-/// #[tokio::main]
-/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-///     test_url("https://www.google.com").await?;
-///     Ok(())
-/// }
-/// ```
-async fn test_url(url: &str) -> Result<(), Box<dyn std::error::Error>> {
-    /*
-    FIXME:
-    Improved error handling, add more status codes
-     */
-    let response_result = reqwest::get(url).await;
-
-    match response_result {
-        Ok(response) => match response.status() {
-            StatusCode::OK
-            | StatusCode::CREATED
-            | StatusCode::ACCEPTED
-            | StatusCode::NON_AUTHORITATIVE_INFORMATION
-            | StatusCode::NO_CONTENT
-            | StatusCode::RESET_CONTENT
-            | StatusCode::PARTIAL_CONTENT
-            | StatusCode::MULTI_STATUS
-            | StatusCode::ALREADY_REPORTED
-            | StatusCode::IM_USED => {
-                println!("{} is {}", url.blue(), "available".green());
+/// Runs the command selected by `cli`. Returns whether everything checked
+/// passed, or an error if panther itself could not do its job.
+async fn run(cli: Cli) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut passed = true;
+    if cli.no_color {
+        colored::control::set_override(false);
+    }
+    let verbosity = Verbosity::new(cli.quiet, cli.verbose);
+    // Used for everything but the checks themselves, which get their own.
+    let mut client = reqwest::Client::builder();
+    if let Some(proxy) = &cli.proxy {
+        client = client.proxy(reqwest::Proxy::all(proxy)?);
+    }
+    for bundle in &cli.cacert {
+        for cert in reqwest::Certificate::from_pem_bundle(&bundle.0)? {
+            client = client.add_root_certificate(cert);
+        }
+    }
+    let client = client.danger_accept_invalid_certs(cli.insecure).build()?;
+    let ca_certs: Vec<Vec<u8>> = cli.cacert.iter().map(|bundle| bundle.0.clone()).collect();
+    match cli.command {
+        Command::Download { .. } if cli.read_only => {
+            return Err("download writes the index to disk, which --read-only forbids".into());
+        }
+        Command::Check(args) if cli.read_only && args.capture_failures.is_some() => {
+            return Err("--capture-failures writes to disk, which --read-only forbids".into());
+        }
+        Command::AuditRepo { git: Some(_), .. } if cli.read_only => {
+            return Err("--git clones the repository to disk, which --read-only forbids".into());
+        }
+        Command::Report { html: Some(_), .. } if cli.read_only => {
+            return Err("--html writes the report to disk, which --read-only forbids".into());
+        }
+        Command::Download { index_url, output } => {
+            download_json_with_client(&client, &index_url, &output).await?;
+            print_saved("File downloaded successfully to:", &output, verbosity);
+        }
+        Command::Check(args) => {
+            let config = match &args.config {
+                Some(path) => Config::load(path)?,
+                None => Config::default(),
+            };
+            let options = CheckOptions {
+                proxy: cli.proxy.clone(),
+                ca_certs: ca_certs.clone(),
+                insecure: cli.insecure,
+                dns: cli.dns.clone(),
+                keep_headers: verbosity == Verbosity::Verbose,
+                ..args.options(&config)
+            };
+            let checkers = args
+                .ip_versions()
+                .into_iter()
+                .map(|ip_version| {
+                    Checker::new(CheckOptions {
+                        ip_version,
+                        ..options.clone()
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            #[cfg(feature = "chaos")]
+            if let Some(rate) = args.chaos.filter(|rate| *rate > 0.0) {
+                let warning = format!(
+                    "chaos mode: {}% of checks fail on purpose, whatever the sources do",
+                    rate * 100.0
+                );
+                print_warning(&warning);
             }
-            status if status.is_server_error() => {
-                println!("{} is {}", url.blue(), "not available".red());
+            if args.max_memory.is_some() && resident_memory().is_none() {
+                print_warning("--max-memory is not supported on this platform, ignoring it");
             }
-            other_status => {
-                println!("{} responded {}", url.blue(), other_status.as_str().yellow());
+            let annotations = match &args.annotations {
+                Some(path) => read_annotations(path)?,
+                None => Vec::new(),
+            };
+            if let Some(dir) = &args.capture_failures {
+                std::fs::create_dir_all(dir)?;
             }
-        },
-        Err(e) => {
-            eprintln!("{}", e.to_string().red());
+            let parse_error = RefCell::new(None);
+            let targets = if args.urls.is_empty() {
+                load_index(&args.index, &client, cli.read_only, verbosity, &mut passed)
+                    .await?
+                    .filter_map(|item| {
+                        future::ready(match item {
+                            Ok((repo, extension)) => Some(stream::iter(
+                                extension
+                                    .sources
+                                    .iter()
+                                    .map(|src| Target {
+                                        repo: repo.clone(),
+                                        ..Target::from_source(&extension, src)
+                                    })
+                                    .collect::<Vec<_>>(),
+                            )),
+                            Err(e) => {
+                                parse_error.replace(Some(e));
+                                None
+                            }
+                        })
+                    })
+                    .flatten()
+                    .boxed_local()
+            } else {
+                stream::iter(args.urls.iter().cloned().map(Target::from)).boxed_local()
+            };
+            // The bar is for people watching a terminal, and would garble the
+            // progress already reported on stderr.
+            let show_bar = !args.no_progress
+                && verbosity != Verbosity::Quiet
+                && args.progress_format.is_none()
+                && std::io::stdout().is_terminal();
+            let counted = args.progress_format.is_some() || show_bar;
+            let (mut results, total) = match (&checkers[..], counted) {
+                ([checker], false) => (checker.check_all(targets).boxed_local(), None),
+                (checkers, _) => {
+                    // How many checks remain is only known once every target
+                    // is, and each IP version goes through all of them.
+                    let targets: Vec<Target> = targets.collect().await;
+                    let total = targets.len() * checkers.len();
+                    let results = checkers.iter().map(|checker| {
+                        checker
+                            .check_all(stream::iter(targets.clone()))
+                            .boxed_local()
+                    });
+                    (stream::select_all(results).boxed_local(), Some(total))
+                }
+            };
+            let mut progress = args
+                .progress_format
+                .zip(total)
+                .map(|(ProgressFormat::Json, total)| Progress::new(total));
+            let bar = total.filter(|_| show_bar).map(progress_bar);
+            let mut failed = 0;
+            let interval = args.progress_interval;
+            let mut ticker = progress
+                .as_ref()
+                .and_then(|_| tokio::time::Instant::now().checked_add(interval))
+                .map(|start| tokio::time::interval_at(start, interval));
+            let mut records = Vec::new();
+            let mut latencies = Vec::new();
+            loop {
+                let (target, result) = tokio::select! {
+                    item = results.next() => match item {
+                        Some(item) => item,
+                        None => break,
+                    },
+                    _ = async {
+                        match &mut ticker {
+                            Some(ticker) => ticker.tick().await,
+                            None => std::future::pending().await,
+                        }
+                    } => {
+                        if let Some(progress) = &progress {
+                            progress.report("progress")?;
+                        }
+                        continue;
+                    }
+                };
+                passed &= result.status.is_available();
+                failed += usize::from(!result.status.is_available());
+                if let Some(bar) = &bar {
+                    advance(bar, failed);
+                }
+                if let Some(progress) = &mut progress {
+                    progress.completed += 1;
+                    progress.failed += usize::from(!result.status.is_available());
+                }
+                if let Some(dir) = &args.capture_failures {
+                    if !result.status.is_available() && rand::random::<f64>() < args.sample {
+                        if let Err(e) = save_capture(dir, &result) {
+                            suspended(bar.as_ref(), || {
+                                print_warning(&format!("could not capture {}: {}", result.url, e))
+                            });
+                        }
+                    }
+                }
+                if let Some(total) = result.total {
+                    latencies.push((result.url.clone(), total));
+                }
+                match args.format {
+                    Format::Text => suspended(bar.as_ref(), || print_result(&result, verbosity)),
+                    Format::Json | Format::Csv | Format::Markdown => {
+                        records.push(ResultRecord::new(target, &result))
+                    }
+                    Format::Ndjson => suspended(bar.as_ref(), || {
+                        write_ndjson(
+                            std::io::stdout().lock(),
+                            &ResultRecord::new(target, &result),
+                        )
+                    })?,
+                }
+                if let (Some(limit), Some(used)) = (args.max_memory, resident_memory()) {
+                    if used > limit {
+                        return Err(format!(
+                            "memory use ({} bytes) exceeded --max-memory ({} bytes)",
+                            used, limit
+                        )
+                        .into());
+                    }
+                }
+            }
+            if let Some(bar) = &bar {
+                bar.finish_and_clear();
+            }
+            if let Some(e) = parse_error.take() {
+                return Err(e.into());
+            }
+            if let Some(progress) = &progress {
+                progress.report("done")?;
+            }
+            annotate(&mut records, &annotations);
+            match args.format {
+                Format::Text if verbosity == Verbosity::Quiet => {}
+                Format::Text => {
+                    if let Some(summary) = LatencySummary::new(latencies, args.slowest) {
+                        print_latency(&summary);
+                    }
+                }
+                Format::Ndjson => {}
+                Format::Json => write_json(std::io::stdout().lock(), &records)?,
+                Format::Csv => write_csv(std::io::stdout().lock(), &records)?,
+                Format::Markdown => write_markdown(std::io::stdout().lock(), &records)?,
+            }
+        }
+        Command::List { index } => {
+            let mut extensions =
+                pin!(load_index(&index, &client, cli.read_only, verbosity, &mut passed).await?);
+            while let Some(extension) = extensions.next().await {
+                let (repo, extension) = extension?;
+                print_extension(repo.as_deref(), &extension);
+            }
+        }
+        Command::TryReplace {
+            source_id,
+            new_url,
+            config,
+            timeout,
+            paths,
+            index,
+        } => {
+            let mut current = None;
+            let mut extensions =
+                pin!(load_index(&index, &client, cli.read_only, verbosity, &mut passed).await?);
+            while let Some(extension) = extensions.next().await {
+                let (_, extension) = extension?;
+                if let Some(src) = extension.sources.iter().find(|src| src.id == source_id) {
+                    current = Some(src.base_url.clone());
+                    print_source(src, &extension);
+                    break;
+                }
+            }
+            let Some(current) = current else {
+                return Err(format!("no source with id {} in the index", source_id).into());
+            };
+            let config = match &config {
+                Some(path) => Config::load(path)?,
+                None => Config::default(),
+            };
+            let checker = Checker::new(CheckOptions {
+                // Every path is checked on its own, see below.
+                paths: Vec::new(),
+                timeout: Some(timeout),
+                accept: config.accept_policy(None),
+                headers: config.headers.clone(),
+                domain_headers: config.domain_headers(),
+                proxy: cli.proxy.clone(),
+                ca_certs: ca_certs.clone(),
+                insecure: cli.insecure,
+                dns: cli.dns.clone(),
+                trace_timing: true,
+                ..CheckOptions::default()
+            })?;
+            let mut rows = Vec::new();
+            for path in config.probe_paths(&paths) {
+                let (old, new) = future::join(
+                    checker.check(&join_path(&current, &path)),
+                    checker.check(&join_path(&new_url, &path)),
+                )
+                .await;
+                passed &= new.status.is_available() || !old.status.is_available();
+                rows.push((path, old, new));
+            }
+            print_comparison(&current, &new_url, &rows);
+        }
+        Command::Export {
+            format,
+            interval,
+            index,
+        } => {
+            // Sources sharing a URL would only make duplicate monitors.
+            let mut seen = HashSet::new();
+            let mut targets = Vec::new();
+            let mut extensions =
+                pin!(load_index(&index, &client, cli.read_only, verbosity, &mut passed).await?);
+            while let Some(extension) = extensions.next().await {
+                let (repo, extension) = extension?;
+                for src in &extension.sources {
+                    if seen.insert(src.base_url.clone()) {
+                        targets.push(Target {
+                            repo: repo.clone(),
+                            ..Target::from_source(&extension, src)
+                        });
+                    }
+                }
+            }
+            match format {
+                ExportFormat::UptimeKuma => {
+                    write_uptime_kuma(std::io::stdout().lock(), &targets, interval)?
+                }
+                ExportFormat::Gatus => write_gatus(std::io::stdout().lock(), &targets, interval)?,
+            }
+        }
+        Command::Coverage { index } => {
+            let mut by_repo: BTreeMap<Option<String>, Vec<Extension>> = BTreeMap::new();
+            let mut extensions =
+                pin!(load_index(&index, &client, cli.read_only, verbosity, &mut passed).await?);
+            while let Some(extension) = extensions.next().await {
+                let (repo, extension) = extension?;
+                by_repo.entry(repo).or_default().push(extension);
+            }
+            for (repo, extensions) in &by_repo {
+                let sources: usize = extensions.iter().map(|e| e.sources.len()).sum();
+                let name = match (repo, &index.index) {
+                    (Some(repo), _) => repo.clone(),
+                    (None, Some(path)) => path.display().to_string(),
+                    (None, None) => index.index_url.clone(),
+                };
+                let counts = Some((extensions.len(), sources));
+                print_coverage(&name, counts, &field_coverage(extensions));
+            }
+            if by_repo.len() > 1 {
+                let coverage = field_coverage(by_repo.values().flatten());
+                print_coverage("All repositories", None, &coverage);
+            }
+        }
+        Command::Diff { old, new } => {
+            let old = read_json_from_file(old)?;
+            let new = read_json_from_file(new)?;
+            for change in diff_indexes(&old, &new) {
+                print_change(&change);
+            }
+        }
+        Command::Validate { index } => {
+            let text = if index.starts_with("http://") || index.starts_with("https://") {
+                let response = client.get(&index).send().await?;
+                if !response.status().is_success() {
+                    return Err(PantherError::Http(response.status()).into());
+                }
+                response.text().await?
+            } else {
+                std::fs::read_to_string(&index)?
+            };
+            let violations = validate_index(&text)?;
+            print_violations(&index, &violations, verbosity);
+            passed = violations.is_empty();
+        }
+        Command::Report {
+            input,
+            html,
+            annotations,
+        } => {
+            let mut records = read_records(&input)?;
+            if let Some(path) = annotations {
+                annotate(&mut records, &read_annotations(path)?);
+            }
+            match html {
+                Some(path) => {
+                    write_html(
+                        std::io::BufWriter::new(std::fs::File::create(&path)?),
+                        &records,
+                    )?;
+                    print_saved("Report written to:", &path, verbosity);
+                }
+                None => write_html(std::io::stdout().lock(), &records)?,
+            }
+        }
+        Command::AuditRepo {
+            git,
+            branch,
+            path,
+            index_url,
+        } => {
+            let published = fetch_json_with_client(&client, &index_url).await?;
+            let findings = match (git, path) {
+                (_, Some(path)) => audit_repo(&published, path)?,
+                (Some(url), None) => {
+                    let dir =
+                        std::env::temp_dir().join(format!("panther-audit-{}", std::process::id()));
+                    let status = std::process::Command::new("git")
+                        .args([
+                            "clone", "--quiet", "--depth", "1", "--branch", &branch, &url,
+                        ])
+                        .arg(&dir)
+                        .status()?;
+                    if !status.success() {
+                        return Err(format!("git clone of {} failed ({})", url, status).into());
+                    }
+                    let findings = audit_repo(&published, &dir);
+                    std::fs::remove_dir_all(&dir)?;
+                    findings?
+                }
+                (None, None) => unreachable!("clap requires --git or --path"),
+            };
+            for finding in &findings {
+                print_finding(finding);
+            }
+            passed = findings.is_empty();
+        }
+        Command::VerifyMirror {
+            url,
+            upstream,
+            sample,
+        } => {
+            let report = verify_mirror_with_client(&client, &url, &upstream, sample).await?;
+            print_mirror(&report, verbosity);
+            passed = report.is_identical();
         }
     }
-    Ok(())
+    Ok(passed)
 }
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let url =
-        "https://raw.githubusercontent.com/keiyoushi/extensions/refs/heads/repo/index.min.json";
-    let output_path = "index.min.json";
-    download_json_github(url, output_path).await?;
-    println!("File downloaded successfully to: {}", output_path);
-    //Change unwrap
-    let json = read_json_from_file("./index.min.json").unwrap();
-    for extension in json.iter() {
-        if extension.lang == "es" {
-            for src in extension.sources.iter() {
-                test_url(&src.base_url).await?;
-            }
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let no_fail = cli.no_fail;
+    match run(cli).await {
+        Ok(passed) if passed || no_fail => ExitCode::SUCCESS,
+        Ok(_) => ExitCode::from(1),
+        Err(e) => {
+            print_error(&e);
+            ExitCode::from(2)
         }
     }
-    Ok(())
 }
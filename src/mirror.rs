@@ -0,0 +1,162 @@
+use crate::{Extension, PantherError};
+use rand::seq::IndexedRandom;
+use sha2::{Digest, Sha256};
+
+/// How a file served by a mirror compares to the upstream one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MirrorStatus {
+    /// The mirror serves the same bytes as upstream.
+    Identical,
+    /// The mirror serves the file, but its content differs.
+    Different,
+    /// The mirror answered `404 Not Found`.
+    Missing,
+}
+
+/// The comparison of one file between a mirror and upstream.
+///
+/// # Fields
+///
+/// * `path`: The path of the file, relative to the repository root.
+/// * `status`: How the mirrored file compares to upstream.
+/// * `upstream_sha256`: The hex SHA-256 of the upstream file.
+/// * `mirror_sha256`: The hex SHA-256 of the mirrored file, if it was served.
+#[derive(Debug, Clone)]
+pub struct FileComparison {
+    pub path: String,
+    pub status: MirrorStatus,
+    pub upstream_sha256: String,
+    pub mirror_sha256: Option<String>,
+}
+
+/// The outcome of [`verify_mirror`]: the index first, then every sampled APK.
+#[derive(Debug, Clone)]
+pub struct MirrorReport {
+    pub index: FileComparison,
+    pub apks: Vec<FileComparison>,
+}
+
+impl MirrorReport {
+    /// Returns whether the index and every sampled APK are identical.
+    pub fn is_identical(&self) -> bool {
+        std::iter::once(&self.index)
+            .chain(&self.apks)
+            .all(|file| file.status == MirrorStatus::Identical)
+    }
+}
+
+/// Checks that a mirror serves the same repository as upstream.
+///
+/// Both `mirror` and `upstream` are repository roots, the directories holding
+/// `index.min.json` and `apk/`. The indexes are compared first, then `sample`
+/// APKs picked at random from the upstream index are downloaded from both and
+/// compared byte for byte through their SHA-256.
+///
+/// # Errors
+///
+/// * If upstream cannot be downloaded or its index cannot be parsed.
+/// * If the mirror fails with anything else than `404 Not Found`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use panther::{verify_mirror, DEFAULT_REPO_URL};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), panther::PantherError> {
+///     let report = verify_mirror("https://mirror.example.com/repo", DEFAULT_REPO_URL, 5).await?;
+///     println!("identical: {}", report.is_identical());
+///     Ok(())
+/// }
+/// ```
+pub async fn verify_mirror(
+    mirror: &str,
+    upstream: &str,
+    sample: usize,
+) -> Result<MirrorReport, PantherError> {
+    // Every file comes from the same two hosts, so connections are reused.
+    verify_mirror_with_client(&reqwest::Client::new(), mirror, upstream, sample).await
+}
+/// Like [`verify_mirror`], sending every request with `client`, e.g. one
+/// configured with a proxy.
+pub async fn verify_mirror_with_client(
+    client: &reqwest::Client,
+    mirror: &str,
+    upstream: &str,
+    sample: usize,
+) -> Result<MirrorReport, PantherError> {
+    let index_path = "index.min.json";
+    let response = success(client.get(join(upstream, index_path)).send().await?)?;
+    let body = response.bytes().await?;
+    let extensions: Vec<Extension> = serde_json::from_slice(&body)?;
+    let index = compare(
+        client,
+        mirror,
+        index_path,
+        format!("{:x}", Sha256::digest(&body)),
+    )
+    .await?;
+
+    let mut apks = Vec::new();
+    let sampled: Vec<&Extension> = extensions
+        .choose_multiple(&mut rand::rng(), sample)
+        .collect();
+    for extension in sampled {
+        let path = format!("apk/{}", extension.apk);
+        let upstream_sha256 = sha256(client.get(join(upstream, &path)).send().await?).await?;
+        apks.push(compare(client, mirror, &path, upstream_sha256).await?);
+    }
+    Ok(MirrorReport { index, apks })
+}
+
+/// Downloads `path` from `mirror` and compares it to the upstream hash.
+async fn compare(
+    client: &reqwest::Client,
+    mirror: &str,
+    path: &str,
+    upstream_sha256: String,
+) -> Result<FileComparison, PantherError> {
+    let response = client.get(join(mirror, path)).send().await?;
+    let mirror_sha256 = if response.status() == reqwest::StatusCode::NOT_FOUND {
+        None
+    } else {
+        Some(sha256(response).await?)
+    };
+    let status = match &mirror_sha256 {
+        None => MirrorStatus::Missing,
+        Some(hash) if *hash == upstream_sha256 => MirrorStatus::Identical,
+        Some(_) => MirrorStatus::Different,
+    };
+    Ok(FileComparison {
+        path: path.to_string(),
+        status,
+        upstream_sha256,
+        mirror_sha256,
+    })
+}
+
+/// Hashes a response body chunk by chunk, so APKs are never held whole.
+async fn sha256(response: reqwest::Response) -> Result<String, PantherError> {
+    let mut response = success(response)?;
+    let mut hasher = Sha256::new();
+    while let Some(chunk) = response.chunk().await? {
+        hasher.update(&chunk);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Turns a non-success response into the matching error.
+fn success(response: reqwest::Response) -> Result<reqwest::Response, PantherError> {
+    if response.status().is_success() {
+        Ok(response)
+    } else if response.status() == reqwest::StatusCode::NOT_FOUND {
+        Err(PantherError::NotFound)
+    } else {
+        Err(PantherError::Http(response.status()))
+    }
+}
+
+/// Joins a repository root and a path inside it.
+fn join(root: &str, path: &str) -> String {
+    format!("{}/{}", root.trim_end_matches('/'), path)
+}
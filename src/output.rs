@@ -0,0 +1,522 @@
+use colored::Colorize;
+use indicatif::{ProgressBar, ProgressStyle};
+use panther::{
+    expiry, AuditFinding, CheckResult, CheckStatus, Extension, FieldCoverage, IndexChange,
+    LatencySummary, MirrorReport, MirrorStatus, PantherError, Source, Timing, Violation,
+};
+use std::{fmt::Display, io::Write, path::Path, time::Instant};
+
+/// How much the terminal output says.
+///
+/// # Variants
+///
+/// * `Quiet`: Only failures, without summaries or progress bar.
+/// * `Normal`: Every result on a line, with warnings and summaries.
+/// * `Verbose`: Also the redirect chain and headers of each response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+impl Verbosity {
+    /// Returns the verbosity asked for by `--quiet` and `--verbose`.
+    pub fn new(quiet: bool, verbose: bool) -> Self {
+        match (quiet, verbose) {
+            (true, _) => Verbosity::Quiet,
+            (false, true) => Verbosity::Verbose,
+            (false, false) => Verbosity::Normal,
+        }
+    }
+}
+
+/// Prints a single check line for `result`, colored according to its status,
+/// followed by warnings and, when `verbosity` is verbose, the redirects and
+/// headers of the response. Available results are left out when quiet.
+pub fn print_result(result: &CheckResult, verbosity: Verbosity) {
+    if verbosity == Verbosity::Quiet && result.status.is_available() {
+        return;
+    }
+    let url = match result.ip_version {
+        Some(version) => format!(
+            "{} {}",
+            result.url.blue(),
+            format!("({})", version).dimmed()
+        ),
+        None => result.url.blue().to_string(),
+    };
+    let mut detail = Vec::new();
+    if !result.resolved.is_empty() {
+        let ips: Vec<String> = result.resolved.iter().map(|ip| ip.to_string()).collect();
+        detail.push(format!("at {}", ips.join(", ")));
+    }
+    if let Some(path) = result.path.as_deref().filter(|path| *path != "/") {
+        detail.push(format!("via {}", path));
+    }
+    if let (Some(ttfb), Some(total)) = (result.ttfb, result.total) {
+        detail.push(format!(
+            "(ttfb {} ms, total {} ms)",
+            ttfb.as_millis(),
+            total.as_millis()
+        ));
+    }
+    if let Some(timing) = &result.timing {
+        let mut steps = Vec::new();
+        if let Some(dns) = timing.dns {
+            steps.push(format!("dns {} ms", dns.as_millis()));
+        }
+        steps.push(format!("connect {} ms", timing.connect.as_millis()));
+        if let Some(tls) = timing.tls {
+            steps.push(format!("tls {} ms", tls.as_millis()));
+        }
+        steps.push(format!("first byte {} ms", timing.ttfb.as_millis()));
+        detail.push(format!("[{}]", steps.join(", ")));
+    }
+    if result.attempts > 1 {
+        detail.push(format!("after {} attempts", result.attempts));
+    }
+    if result.throttled {
+        detail.push("(rate limited)".to_string());
+    }
+    if let Some(final_url) = result.final_url() {
+        detail.push(format!("-> {}", final_url));
+    }
+    let detail = detail.join(" ");
+    match result.status {
+        CheckStatus::Available | CheckStatus::Expiring => {
+            println!("{} is {} {}", url, "available".green(), detail.dimmed());
+        }
+        CheckStatus::NotAvailable => {
+            println!("{} is {} {}", url, "not available".red(), detail.dimmed());
+        }
+        CheckStatus::Unexpected => {
+            let status = result
+                .http_status
+                .map(|s| s.as_str().to_string())
+                .unwrap_or_default();
+            println!("{} responded {} {}", url, status.yellow(), detail.dimmed());
+        }
+        CheckStatus::Reaped => {
+            eprintln!(
+                "{} {} {}",
+                url,
+                "hung and was reaped".red(),
+                detail.dimmed()
+            );
+        }
+        CheckStatus::Timeout => match &result.error {
+            Some(PantherError::Deadline) => {
+                eprintln!("{} {}", url, "not checked before the deadline".yellow());
+            }
+            _ => eprintln!("{} {} {}", url, "timed out".yellow(), detail.dimmed()),
+        },
+        CheckStatus::Error => match &result.error {
+            Some(PantherError::Dns(_) | PantherError::Unresolved { .. }) => {
+                eprintln!(
+                    "{} {} {}",
+                    url,
+                    "could not be resolved".red(),
+                    detail.dimmed()
+                );
+            }
+            Some(e) => eprintln!("{} {}", e.to_string().red(), detail.dimmed()),
+            None => {}
+        },
+    }
+    if let Some(host) = result.moved_to() {
+        println!(
+            "{} {}",
+            url,
+            format!("moved to {}, the index may need updating", host).yellow()
+        );
+    }
+    if result.insecure {
+        println!(
+            "{} {}",
+            url,
+            "answered with a certificate that was not verified".yellow()
+        );
+    }
+    if let Some(e) = &result.timing_error {
+        println!(
+            "{} {}",
+            url,
+            format!("could not be timed step by step: {}", e).yellow()
+        );
+    }
+    if let (CheckStatus::Expiring, Some(certificate)) = (result.status, &result.certificate) {
+        println!(
+            "{} {}",
+            url,
+            format!(
+                "has a certificate for {} from {} that {}",
+                certificate.subject,
+                certificate.issuer,
+                expiry(certificate.days_left())
+            )
+            .yellow()
+        );
+    }
+    if verbosity == Verbosity::Verbose {
+        print_details(result);
+    }
+}
+/// Prints the redirect chain of `result` and the headers of its response,
+/// indented under its check line.
+fn print_details(result: &CheckResult) {
+    for (hop, url) in result.redirects.iter().enumerate().skip(1) {
+        println!("  {} {}", format!("redirect {}:", hop).dimmed(), url);
+    }
+    if let Some(capture) = &result.capture {
+        for (name, value) in &capture.headers {
+            let value = String::from_utf8_lossy(value.as_bytes());
+            println!("  {} {}", format!("{}:", name).dimmed(), value);
+        }
+    }
+}
+/// Prints the checks of the current and candidate base URLs of a source side
+/// by side, one row per probe path, followed by the connection timings of the
+/// homepage.
+pub fn print_comparison(
+    current: &str,
+    candidate: &str,
+    rows: &[(String, CheckResult, CheckResult)],
+) {
+    const WIDTH: usize = 40;
+    let outcome = |result: &CheckResult| {
+        let text = match (&result.http_status, &result.error, result.total) {
+            (Some(code), _, Some(total)) => {
+                format!("{} {} ms", code.as_u16(), total.as_millis())
+            }
+            (_, Some(e), _) => e.to_string(),
+            _ => String::new(),
+        };
+        let text = format!("{:<WIDTH$.WIDTH$}", text);
+        match result.status {
+            CheckStatus::Available => text.green(),
+            CheckStatus::Expiring | CheckStatus::Unexpected | CheckStatus::Timeout => text.yellow(),
+            CheckStatus::NotAvailable | CheckStatus::Reaped | CheckStatus::Error => text.red(),
+        }
+    };
+    let step = |duration: Option<std::time::Duration>| {
+        let text = duration.map_or_else(|| "-".to_string(), |d| format!("{} ms", d.as_millis()));
+        format!("{:<WIDTH$}", text)
+    };
+    println!(
+        "{:<12} {} {}",
+        "",
+        format!("{:<WIDTH$.WIDTH$}", current).bold(),
+        format!("{:<WIDTH$.WIDTH$}", candidate).bold()
+    );
+    for (path, old, new) in rows {
+        println!("{:<12} {} {}", path, outcome(old), outcome(new));
+    }
+    let Some((_, old, new)) = rows.first() else {
+        return;
+    };
+    let steps = |timing: Option<Timing>| {
+        [
+            timing.and_then(|timing| timing.dns),
+            timing.map(|timing| timing.connect),
+            timing.and_then(|timing| timing.tls),
+            timing.map(|timing| timing.ttfb),
+        ]
+    };
+    let labels = ["dns", "connect", "tls", "first byte"];
+    for ((label, old), new) in labels.iter().zip(steps(old.timing)).zip(steps(new.timing)) {
+        println!(
+            "{:<12} {} {}",
+            label,
+            step(old).dimmed(),
+            step(new).dimmed()
+        );
+    }
+    for (label, result) in [("current", old), ("candidate", new)] {
+        if let Some(host) = result.moved_to() {
+            println!("{} {}", label, format!("redirects to {}", host).yellow());
+        }
+        if result.insecure {
+            println!("{} {}", label, "certificate not verified".yellow());
+        }
+    }
+}
+/// Builds the progress bar of a `panther check` run of `total` checks,
+/// drawn on stderr, with room for the failure count as its message. It is
+/// only colored if the rest of the output is.
+pub fn progress_bar(total: usize) -> ProgressBar {
+    let template = match colored::control::SHOULD_COLORIZE.should_colorize() {
+        true => "{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {pos}/{len} (ETA {eta}) {msg}",
+        false => "{spinner} [{elapsed_precise}] [{wide_bar}] {pos}/{len} (ETA {eta}) {msg}",
+    };
+    let style = ProgressStyle::with_template(template)
+        .expect("valid progress bar template")
+        .progress_chars("=> ");
+    let bar = ProgressBar::new(total as u64).with_style(style);
+    bar.enable_steady_tick(std::time::Duration::from_millis(100));
+    bar
+}
+
+/// Advances `bar` by one check, showing how many of them `failed` so far.
+pub fn advance(bar: &ProgressBar, failed: usize) {
+    bar.inc(1);
+    if failed > 0 {
+        bar.set_message(format!("{} failed", failed).red().to_string());
+    }
+}
+
+/// Runs `print`, hiding `bar` meanwhile so that what is printed does not get
+/// mixed with it.
+pub fn suspended<R>(bar: Option<&ProgressBar>, print: impl FnOnce() -> R) -> R {
+    match bar {
+        Some(bar) => bar.suspend(print),
+        None => print(),
+    }
+}
+
+/// How far a `panther check` run got, reported with `--progress-format`.
+pub struct Progress {
+    pub total: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub start: Instant,
+}
+
+impl Progress {
+    /// Starts tracking a run of `total` checks.
+    pub fn new(total: usize) -> Self {
+        Progress {
+            total,
+            completed: 0,
+            failed: 0,
+            start: Instant::now(),
+        }
+    }
+
+    /// Writes a line of JSON to stderr describing the progress so far, as an
+    /// `event` such as "progress" or "done". The time left is extrapolated
+    /// from the pace of the checks completed so far.
+    pub fn report(&self, event: &str) -> std::io::Result<()> {
+        let elapsed = self.start.elapsed();
+        let remaining = self.total.saturating_sub(self.completed);
+        let eta = (self.completed > 0).then(|| {
+            elapsed
+                .mul_f64(remaining as f64 / self.completed as f64)
+                .as_millis()
+        });
+        let line = serde_json::json!({
+            "event": event,
+            "completed": self.completed,
+            "remaining": remaining,
+            "total": self.total,
+            "failed": self.failed,
+            "elapsed_ms": elapsed.as_millis(),
+            "eta_ms": eta,
+        });
+        writeln!(std::io::stderr().lock(), "{}", line)
+    }
+}
+/// Prints how many extensions or sources miss each field of `coverage`, under
+/// `name` and, if given, how many extensions and sources it covers.
+pub fn print_coverage(name: &str, counts: Option<(usize, usize)>, coverage: &[FieldCoverage]) {
+    match counts {
+        Some((extensions, sources)) => println!(
+            "{} ({} extensions, {} sources)",
+            name.bold(),
+            extensions,
+            sources
+        ),
+        None => println!("{}", name.bold()),
+    }
+    for field in coverage {
+        let line = format!(
+            "  {:<22} {:>6.1}% {:>6} missing of {}",
+            field.field,
+            field.percent(),
+            field.missing,
+            field.total
+        );
+        if field.missing == 0 {
+            println!("{}", line.dimmed());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+/// Prints the latency `summary` of a run.
+pub fn print_latency(summary: &LatencySummary) {
+    println!(
+        "\n{} over {} responses: min {} ms, median {} ms, p95 {} ms, max {} ms",
+        "Latency".bold(),
+        summary.count,
+        summary.min.as_millis(),
+        summary.median.as_millis(),
+        summary.p95.as_millis(),
+        summary.max.as_millis()
+    );
+    if !summary.slowest.is_empty() {
+        println!("{}", "Slowest hosts:".bold());
+    }
+    for (host, latency) in &summary.slowest {
+        println!(
+            "  {} {}",
+            host.blue(),
+            format!("{} ms", latency.as_millis()).dimmed()
+        );
+    }
+}
+/// Prints a warning on stderr, whatever the verbosity.
+pub fn print_warning(message: &str) {
+    eprintln!("{}", message.yellow());
+}
+/// Prints the error that stopped panther on stderr.
+pub fn print_error(e: &dyn Display) {
+    eprintln!("{} {}", "Error:".red(), e);
+}
+/// Prints `message` followed by the path of a file that was written, on
+/// stderr so that stdout only carries results. Nothing is printed when quiet.
+pub fn print_saved(message: &str, path: &Path, verbosity: Verbosity) {
+    if verbosity != Verbosity::Quiet {
+        eprintln!("{} {}", message, path.display());
+    }
+}
+/// Prints an extension of the index, found in `repo` if given, and its
+/// sources indented under it.
+pub fn print_extension(repo: Option<&str>, extension: &Extension) {
+    print!("{} {}", extension.name.bold(), extension.version.dimmed());
+    match repo {
+        Some(repo) => println!(" {}", repo.blue()),
+        None => println!(),
+    }
+    for src in extension.sources.iter() {
+        println!(
+            "  {} [{}] {} {}",
+            src.name,
+            src.lang,
+            src.id.dimmed(),
+            src.base_url.blue()
+        );
+    }
+}
+/// Prints which source of which extension a command works on.
+pub fn print_source(src: &Source, extension: &Extension) {
+    println!("{} ({}) in {}", src.name.bold(), src.lang, extension.name);
+}
+/// Prints a change between two snapshots of an index.
+pub fn print_change(change: &IndexChange) {
+    match change {
+        IndexChange::Added { pkg, name, version } => {
+            println!(
+                "{} {} {} {}",
+                "+".green(),
+                name.bold(),
+                version,
+                pkg.dimmed()
+            )
+        }
+        IndexChange::Removed { pkg, name } => {
+            println!("{} {} {}", "-".red(), name.bold(), pkg.dimmed())
+        }
+        IndexChange::VersionChanged {
+            pkg,
+            name,
+            old,
+            new,
+        } => println!(
+            "{} {} {} -> {} {}",
+            "~".yellow(),
+            name.bold(),
+            old,
+            new,
+            pkg.dimmed()
+        ),
+        IndexChange::BaseUrlChanged {
+            pkg,
+            source,
+            id,
+            old,
+            new,
+        } => println!(
+            "{} {} [{}] {} -> {} {}",
+            "~".yellow(),
+            source,
+            id.dimmed(),
+            old.blue(),
+            new.blue(),
+            pkg.dimmed()
+        ),
+    }
+}
+/// Prints every violation found in `index`, or that it is valid unless
+/// quiet.
+pub fn print_violations(index: &str, violations: &[Violation], verbosity: Verbosity) {
+    for violation in violations {
+        println!(
+            "{}:{}: {}{} {}",
+            index,
+            violation.line,
+            violation
+                .extension
+                .as_deref()
+                .map(|name| format!("{} ", name.bold()))
+                .unwrap_or_default(),
+            violation.field.yellow(),
+            violation.message.red()
+        );
+    }
+    if violations.is_empty() && verbosity != Verbosity::Quiet {
+        println!("{} is {}", index, "valid".green());
+    }
+}
+/// Prints a difference between a repository and its published index.
+pub fn print_finding(finding: &AuditFinding) {
+    match finding {
+        AuditFinding::MissingApk { pkg, apk } => {
+            println!("{} {} is {}", pkg, apk, "missing".red())
+        }
+        AuditFinding::OrphanedApk { apk } => {
+            println!("{} is {}", apk, "orphaned".yellow())
+        }
+        AuditFinding::MissingIcon { pkg } => {
+            println!("{} has {}", pkg, "no icon".yellow())
+        }
+        AuditFinding::NotInRepo { pkg } => {
+            println!("{} is {}", pkg, "published but not in the repository".red())
+        }
+        AuditFinding::NotPublished { pkg } => {
+            println!(
+                "{} is {}",
+                pkg,
+                "in the repository but not published".yellow()
+            )
+        }
+        AuditFinding::VersionMismatch {
+            pkg,
+            published,
+            repo,
+        } => println!(
+            "{} is published as {} but is {} in the repository",
+            pkg,
+            published.red(),
+            repo.green()
+        ),
+    }
+}
+/// Prints how each file of a mirror compares to upstream. Identical files
+/// are left out when quiet.
+pub fn print_mirror(report: &MirrorReport, verbosity: Verbosity) {
+    for file in std::iter::once(&report.index).chain(&report.apks) {
+        match file.status {
+            MirrorStatus::Identical if verbosity == Verbosity::Quiet => {}
+            MirrorStatus::Identical => println!("{} is {}", file.path, "identical".green()),
+            MirrorStatus::Different => println!(
+                "{} {} (upstream {}, mirror {})",
+                file.path,
+                "differs".red(),
+                file.upstream_sha256.dimmed(),
+                file.mirror_sha256.as_deref().unwrap_or_default().dimmed()
+            ),
+            MirrorStatus::Missing => println!("{} is {}", file.path, "missing".red()),
+        }
+    }
+}
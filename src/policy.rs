@@ -0,0 +1,145 @@
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::{collections::HashMap, fmt, ops::RangeInclusive, str::FromStr};
+
+/// A set of HTTP status codes, written as a comma-separated list of codes and
+/// ranges such as `200-299,403`.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::StatusSet;
+/// use reqwest::StatusCode;
+///
+/// let set: StatusSet = "200-299,403".parse().unwrap();
+/// assert!(set.contains(StatusCode::NO_CONTENT));
+/// assert!(set.contains(StatusCode::FORBIDDEN));
+/// assert!(!set.contains(StatusCode::NOT_FOUND));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct StatusSet(Vec<RangeInclusive<u16>>);
+
+impl StatusSet {
+    /// Returns whether `status` is in the set.
+    pub fn contains(&self, status: StatusCode) -> bool {
+        self.0.iter().any(|range| range.contains(&status.as_u16()))
+    }
+}
+
+impl Default for StatusSet {
+    /// Every success status.
+    fn default() -> Self {
+        StatusSet(vec![200..=299])
+    }
+}
+
+impl FromStr for StatusSet {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let code = |code: &str| {
+            code.trim()
+                .parse::<u16>()
+                .ok()
+                .filter(|code| (100..=999).contains(code))
+                .ok_or_else(|| format!("invalid status code: {}", code.trim()))
+        };
+        let mut ranges = Vec::new();
+        for part in value.split(',').filter(|part| !part.trim().is_empty()) {
+            let range = match part.split_once('-') {
+                Some((start, end)) => code(start)?..=code(end)?,
+                None => code(part)?..=code(part)?,
+            };
+            if range.is_empty() {
+                return Err(format!("empty status range: {}", part.trim()));
+            }
+            ranges.push(range);
+        }
+        if ranges.is_empty() {
+            return Err("no status code given".to_string());
+        }
+        Ok(StatusSet(ranges))
+    }
+}
+
+impl TryFrom<String> for StatusSet {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for StatusSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, range) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            if range.start() == range.end() {
+                write!(f, "{}", range.start())?;
+            } else {
+                write!(f, "{}-{}", range.start(), range.end())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Decides which status codes count as available.
+///
+/// Many sources sit behind Cloudflare and answer `403` while being perfectly
+/// alive, so the codes accepted can be widened, for every source or only for
+/// some domains.
+///
+/// # Fields
+///
+/// * `default`: The codes accepted for every domain without a rule.
+/// * `domains`: Codes accepted for a domain, in lower case, and its
+///   subdomains, replacing `default`. When several rules match, the most
+///   specific domain wins.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::AcceptPolicy;
+/// use reqwest::StatusCode;
+///
+/// let mut policy = AcceptPolicy::default();
+/// policy.domains.insert("example.com".to_string(), "200-299,403".parse().unwrap());
+///
+/// assert!(policy.accepts("https://www.example.com/latest", StatusCode::FORBIDDEN));
+/// assert!(!policy.accepts("https://example.org", StatusCode::FORBIDDEN));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AcceptPolicy {
+    pub default: StatusSet,
+    pub domains: HashMap<String, StatusSet>,
+}
+
+impl AcceptPolicy {
+    /// Returns the codes accepted for `url`.
+    pub fn for_url(&self, url: &str) -> &StatusSet {
+        for_domain(&self.domains, url).unwrap_or(&self.default)
+    }
+
+    /// Returns whether `status` counts as available for `url`.
+    pub fn accepts(&self, url: &str, status: StatusCode) -> bool {
+        self.for_url(url).contains(status)
+    }
+}
+
+/// Returns the value of `rules`, keyed by lower case domain, for the host of
+/// `url`. A rule covers its domain and subdomains, and when several match,
+/// the most specific domain wins.
+pub(crate) fn for_domain<'a, T>(rules: &'a HashMap<String, T>, url: &str) -> Option<&'a T> {
+    let host = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_ascii_lowercase))?;
+    rules
+        .iter()
+        .filter(|(domain, _)| host == **domain || host.ends_with(&format!(".{}", domain)))
+        .max_by_key(|(domain, _)| domain.len())
+        .map(|(_, value)| value)
+}
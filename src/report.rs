@@ -0,0 +1,497 @@
+use crate::{CheckResult, CheckStatus, IpVersion, PantherError, Target};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, Write},
+    path::Path,
+};
+
+/// A flat, serializable record of one checked source.
+///
+/// This is the result model shared by every report format: it combines the
+/// [`Target`] that was checked with the outcome of the check, with durations
+/// in milliseconds and errors rendered as text.
+///
+/// # Fields
+///
+/// * `extension`: The name of the extension providing the source.
+/// * `source`: The name of the source.
+/// * `source_id`: The unique identifier of the source.
+/// * `lang`: The language of the source.
+/// * `repo`: The repository whose index lists the source.
+/// * `url`: The checked URL.
+/// * `status`: How the URL was classified.
+/// * `http_status`: The HTTP status code received, if any.
+/// * `path`: The probe path that produced the reported response.
+/// * `ttfb_ms`: Time to first byte, in milliseconds.
+/// * `latency_ms`: Total time of the request, in milliseconds.
+/// * `attempts`: How many requests were sent, retries included.
+/// * `method`: The HTTP method of the reported request.
+/// * `final_url`: The URL redirects ended on, if the request was redirected.
+/// * `redirects`: The redirect chain, URLs separated by ` -> `.
+/// * `moved_to`: The host redirects ended on, when it differs from the host
+///   of `url`.
+/// * `dns_ms`, `connect_ms`, `tls_ms`: How long DNS resolution, the TCP
+///   connection and the TLS handshake took, when timing was traced.
+/// * `insecure`: Whether the response came over TLS without its certificate
+///   being verified.
+/// * `cert_subject`, `cert_issuer`: Who the TLS certificate of the server
+///   was issued to and by.
+/// * `cert_days_left`: How many days were left before the certificate
+///   expired when it was checked, negative once it has.
+/// * `ip_version`: The only IP version the URL was checked over, if any.
+/// * `resolved`: The addresses its host resolved to, comma separated.
+/// * `throttled`: Whether the server answered `429 Too Many Requests`.
+/// * `error`: The error message, if the check failed without a response.
+/// * `note`: Context added by a human, from an [`Annotation`].
+/// * `link`: A related URL (incident, tracking issue), from an [`Annotation`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultRecord {
+    pub extension: Option<String>,
+    pub source: Option<String>,
+    pub source_id: Option<String>,
+    pub lang: Option<String>,
+    #[serde(default)]
+    pub repo: Option<String>,
+    pub url: String,
+    pub status: CheckStatus,
+    pub http_status: Option<u16>,
+    pub path: Option<String>,
+    pub ttfb_ms: Option<u64>,
+    pub latency_ms: Option<u64>,
+    pub attempts: u32,
+    #[serde(default)]
+    pub method: Option<String>,
+    #[serde(default)]
+    pub final_url: Option<String>,
+    #[serde(default)]
+    pub redirects: Option<String>,
+    #[serde(default)]
+    pub moved_to: Option<String>,
+    #[serde(default)]
+    pub dns_ms: Option<u64>,
+    #[serde(default)]
+    pub connect_ms: Option<u64>,
+    #[serde(default)]
+    pub tls_ms: Option<u64>,
+    #[serde(default)]
+    pub insecure: bool,
+    #[serde(default)]
+    pub cert_subject: Option<String>,
+    #[serde(default)]
+    pub cert_issuer: Option<String>,
+    #[serde(default)]
+    pub cert_days_left: Option<i64>,
+    #[serde(default)]
+    pub ip_version: Option<IpVersion>,
+    #[serde(default)]
+    pub resolved: Option<String>,
+    #[serde(default)]
+    pub throttled: bool,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub link: Option<String>,
+}
+
+impl ResultRecord {
+    /// Builds the record for `result`, the outcome of checking `target`.
+    pub fn new(target: Target, result: &CheckResult) -> Self {
+        ResultRecord {
+            extension: target.extension,
+            source: target.source,
+            source_id: target.source_id,
+            lang: target.lang,
+            repo: target.repo,
+            url: target.url,
+            status: result.status,
+            http_status: result.http_status.map(|status| status.as_u16()),
+            path: result.path.clone(),
+            ttfb_ms: result.ttfb.map(|ttfb| ttfb.as_millis() as u64),
+            latency_ms: result.total.map(|total| total.as_millis() as u64),
+            attempts: result.attempts,
+            method: result.method.as_ref().map(|method| method.to_string()),
+            final_url: result.final_url().map(str::to_string),
+            redirects: (!result.redirects.is_empty()).then(|| result.redirects.join(" -> ")),
+            moved_to: result.moved_to(),
+            dns_ms: result
+                .timing
+                .and_then(|timing| timing.dns)
+                .map(|dns| dns.as_millis() as u64),
+            connect_ms: result
+                .timing
+                .map(|timing| timing.connect.as_millis() as u64),
+            tls_ms: result
+                .timing
+                .and_then(|timing| timing.tls)
+                .map(|tls| tls.as_millis() as u64),
+            insecure: result.insecure,
+            cert_subject: result.certificate.as_ref().map(|c| c.subject.clone()),
+            cert_issuer: result.certificate.as_ref().map(|c| c.issuer.clone()),
+            cert_days_left: result.certificate.as_ref().map(|c| c.days_left()),
+            ip_version: result.ip_version,
+            resolved: (!result.resolved.is_empty()).then(|| {
+                let ips: Vec<String> = result.resolved.iter().map(|ip| ip.to_string()).collect();
+                ips.join(", ")
+            }),
+            throttled: result.throttled,
+            error: result.error.as_ref().map(|e| e.to_string()),
+            note: None,
+            link: None,
+        }
+    }
+
+    /// Builds a record of `url` classified as `status`, with every other
+    /// field empty, to be filled in with struct update syntax.
+    pub fn with_status(url: &str, status: CheckStatus) -> Self {
+        ResultRecord {
+            extension: None,
+            source: None,
+            source_id: None,
+            lang: None,
+            repo: None,
+            url: url.to_string(),
+            status,
+            http_status: None,
+            path: None,
+            ttfb_ms: None,
+            latency_ms: None,
+            attempts: 0,
+            method: None,
+            final_url: None,
+            redirects: None,
+            moved_to: None,
+            dns_ms: None,
+            connect_ms: None,
+            tls_ms: None,
+            insecure: false,
+            cert_subject: None,
+            cert_issuer: None,
+            cert_days_left: None,
+            ip_version: None,
+            resolved: None,
+            throttled: false,
+            error: None,
+            note: None,
+            link: None,
+        }
+    }
+}
+
+/// Writes `records` as a pretty-printed JSON array.
+///
+/// # Errors
+///
+/// * If serializing or writing to `writer` fails.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::{write_json, ResultRecord};
+///
+/// let records: Vec<ResultRecord> = Vec::new();
+/// let mut out = Vec::new();
+/// write_json(&mut out, &records).unwrap();
+/// assert_eq!(out, b"[]\n");
+/// ```
+pub fn write_json<W: Write>(mut writer: W, records: &[ResultRecord]) -> Result<(), PantherError> {
+    serde_json::to_writer_pretty(&mut writer, records)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Writes `record` as a single line of JSON, for newline-delimited output
+/// where each result is printed as soon as it is known.
+///
+/// # Errors
+///
+/// * If serializing or writing to `writer` fails.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::{write_ndjson, CheckStatus, ResultRecord};
+///
+/// let record = ResultRecord {
+///     http_status: Some(404),
+///     ..ResultRecord::with_status("https://example.com", CheckStatus::NotAvailable)
+/// };
+/// let mut out = Vec::new();
+/// write_ndjson(&mut out, &record).unwrap();
+/// let line = String::from_utf8(out).unwrap();
+/// assert!(line.starts_with("{\"extension\":null,"));
+/// assert_eq!(line.lines().count(), 1);
+/// ```
+pub fn write_ndjson<W: Write>(mut writer: W, record: &ResultRecord) -> Result<(), PantherError> {
+    serde_json::to_writer(&mut writer, record)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Writes `records` as CSV, with a header row and one row per checked source.
+///
+/// The columns are the fields of [`ResultRecord`], in order. Empty cells stand
+/// for missing values (e.g. no HTTP status after a DNS failure).
+///
+/// # Errors
+///
+/// * If serializing or writing to `writer` fails.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::{write_csv, CheckStatus, ResultRecord};
+///
+/// let records = vec![ResultRecord {
+///     extension: Some("My Extension".to_string()),
+///     http_status: Some(200),
+///     path: Some("/".to_string()),
+///     ttfb_ms: Some(80),
+///     latency_ms: Some(120),
+///     attempts: 1,
+///     method: Some("GET".to_string()),
+///     ..ResultRecord::with_status("https://example.com", CheckStatus::Available)
+/// }];
+/// let mut out = Vec::new();
+/// write_csv(&mut out, &records).unwrap();
+/// let csv = String::from_utf8(out).unwrap();
+/// assert!(csv.starts_with("extension,source,source_id,lang,repo,url,status,"));
+/// assert!(csv.contains("https://example.com,available,200,/,80,120,1,GET,"));
+/// ```
+pub fn write_csv<W: Write>(writer: W, records: &[ResultRecord]) -> Result<(), PantherError> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for record in records {
+        writer.serialize(record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes `records` as a Markdown table grouped by extension, ready to be
+/// pasted into a GitHub issue.
+///
+/// A summary of the run comes first, then one row per source with an emoji
+/// for its status. Extensions are sorted by name and only named on their
+/// first row, along with their repository when known; sources checked
+/// directly by URL come last.
+///
+/// # Errors
+///
+/// * If writing to `writer` fails.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::{write_markdown, CheckStatus, ResultRecord};
+///
+/// let records = vec![ResultRecord {
+///     extension: Some("My Extension".to_string()),
+///     source: Some("My Source".to_string()),
+///     lang: Some("en".to_string()),
+///     http_status: Some(404),
+///     ..ResultRecord::with_status("https://example.com", CheckStatus::NotAvailable)
+/// }];
+/// let mut out = Vec::new();
+/// write_markdown(&mut out, &records).unwrap();
+/// let markdown = String::from_utf8(out).unwrap();
+/// assert!(markdown.contains("**1** sources checked: 0 ok, 1 failed"));
+/// assert!(markdown.contains("| My Extension | My Source | en | https://example.com | ❌ not available | 404 |"));
+/// ```
+pub fn write_markdown<W: Write>(
+    mut writer: W,
+    records: &[ResultRecord],
+) -> Result<(), PantherError> {
+    let ok = records
+        .iter()
+        .filter(|record| record.status.is_available())
+        .count();
+    // `None` sorts first, so direct URLs are keyed to come after extensions.
+    let mut by_extension: BTreeMap<(bool, &str, &str), Vec<&ResultRecord>> = BTreeMap::new();
+    for record in records {
+        let repo = record.repo.as_deref().unwrap_or("");
+        let key = match record.extension.as_deref() {
+            Some(name) => (false, name, repo),
+            None => (true, "", repo),
+        };
+        by_extension.entry(key).or_default().push(record);
+    }
+
+    writeln!(writer, "## Panther report\n")?;
+    writeln!(
+        writer,
+        "**{}** sources checked: {} ok, {} failed\n",
+        records.len(),
+        ok,
+        records.len() - ok
+    )?;
+    writeln!(
+        writer,
+        "| Extension | Source | Language | URL | Status | Details |"
+    )?;
+    writeln!(writer, "| --- | --- | --- | --- | --- | --- |")?;
+    for ((_, extension, repo), records) in by_extension {
+        let extension = match repo {
+            "" => extension.to_string(),
+            repo => format!("{} ({})", extension, repo),
+        };
+        for (i, record) in records.into_iter().enumerate() {
+            let status = match record.status {
+                CheckStatus::Available => "✅ available",
+                CheckStatus::Expiring => "🔒 certificate expiring",
+                CheckStatus::NotAvailable => "❌ not available",
+                CheckStatus::Unexpected => "⚠️ unexpected",
+                CheckStatus::Timeout => "⏱️ timed out",
+                CheckStatus::Reaped => "💀 reaped",
+                CheckStatus::Error => "❌ error",
+            };
+            let details = match (record.http_status, &record.error) {
+                (Some(code), _) => code.to_string(),
+                (None, Some(error)) => error.clone(),
+                (None, None) => String::new(),
+            };
+            let moved = record
+                .moved_to
+                .as_ref()
+                .map(|host| format!("moved to {}", host));
+            let details = [
+                Some(details),
+                record.ip_version.map(|version| format!("over {}", version)),
+                moved,
+                record.throttled.then(|| "rate limited".to_string()),
+                record
+                    .insecure
+                    .then(|| "certificate not verified".to_string()),
+                (record.status == CheckStatus::Expiring)
+                    .then(|| {
+                        record
+                            .cert_days_left
+                            .map(|days_left| format!("certificate {}", expiry(days_left)))
+                    })
+                    .flatten(),
+                record.note.clone(),
+                record.link.clone(),
+            ]
+            .into_iter()
+            .flatten()
+            .filter(|detail| !detail.is_empty())
+            .collect::<Vec<_>>()
+            .join(" — ");
+            writeln!(
+                writer,
+                "| {} | {} | {} | {} | {} | {} |",
+                cell(if i == 0 { &extension } else { "" }),
+                cell(record.source.as_deref().unwrap_or("")),
+                cell(record.lang.as_deref().unwrap_or("")),
+                cell(&record.url),
+                status,
+                cell(&details)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Describes when a certificate with `days_left` expires.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::expiry;
+///
+/// assert_eq!(expiry(3), "expires in 3 days");
+/// assert_eq!(expiry(0), "expires today");
+/// assert_eq!(expiry(-1), "expired yesterday");
+/// ```
+pub fn expiry(days_left: i64) -> String {
+    match days_left {
+        -1 => "expired yesterday".to_string(),
+        ..=-2 => format!("expired {} days ago", -days_left),
+        0 => "expires today".to_string(),
+        1 => "expires tomorrow".to_string(),
+        days => format!("expires in {} days", days),
+    }
+}
+
+/// Makes `text` safe to put in a Markdown table cell.
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Reads records previously written by [`write_json`] from the file at `path`.
+///
+/// # Errors
+///
+/// * If the file cannot be opened.
+/// * If the file is not a JSON array of records.
+pub fn read_records<P: AsRef<Path>>(path: P) -> Result<Vec<ResultRecord>, PantherError> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(serde_json::from_reader(reader)?)
+}
+
+/// Context about a source added by a human, e.g. the incident explaining an
+/// outage or the issue tracking its removal.
+///
+/// An annotation applies to the records whose `source_id` or `url` matches
+/// its own; at least one of them should be set.
+///
+/// # Fields
+///
+/// * `source_id`: The identifier of the annotated source.
+/// * `url`: The annotated URL.
+/// * `note`: Free text shown next to the result.
+/// * `link`: A URL to more context. The HTML report only links http and
+///   https URLs and shows anything else as plain text.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::Annotation;
+///
+/// let json_str = r#"[{"source_id": "123", "link": "https://github.com/keiyoushi/extensions/issues/1"}]"#;
+/// let annotations: Vec<Annotation> = serde_json::from_str(json_str).unwrap();
+/// assert_eq!(annotations[0].source_id.as_deref(), Some("123"));
+/// assert!(annotations[0].note.is_none());
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Annotation {
+    #[serde(default)]
+    pub source_id: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub link: Option<String>,
+}
+
+impl Annotation {
+    /// Returns whether this annotation applies to `record`.
+    pub fn matches(&self, record: &ResultRecord) -> bool {
+        (self.source_id.is_some() && self.source_id == record.source_id)
+            || self.url.as_deref() == Some(record.url.as_str())
+    }
+}
+
+/// Reads a JSON array of annotations from the file at `path`.
+///
+/// # Errors
+///
+/// * If the file cannot be opened.
+/// * If the file is not a JSON array of annotations.
+pub fn read_annotations<P: AsRef<Path>>(path: P) -> Result<Vec<Annotation>, PantherError> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(serde_json::from_reader(reader)?)
+}
+
+/// Copies the note and link of the first matching annotation into every
+/// record. Records without a matching annotation are left untouched.
+pub fn annotate(records: &mut [ResultRecord], annotations: &[Annotation]) {
+    for record in records {
+        if let Some(annotation) = annotations.iter().find(|a| a.matches(record)) {
+            record.note = annotation.note.clone();
+            record.link = annotation.link.clone();
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use rand::Rng;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How failed requests are retried.
+///
+/// Only transient failures are retried: errors for which
+/// [`crate::PantherError::is_retryable`] holds and `5xx` responses. The
+/// delay before attempt `n + 1` is `base_delay * 2^(n - 1)`, capped at
+/// `max_delay`. With `jitter`, a random delay between half and all of that
+/// is used instead, so that many sources failing at once don't all retry in
+/// lockstep.
+///
+/// # Fields
+///
+/// * `max_attempts`: How many times a request is sent at most, the first one
+///   included. `1` disables retrying.
+/// * `base_delay`: The delay before the first retry.
+/// * `max_delay`: The longest delay between two attempts.
+/// * `jitter`: Whether to randomize delays.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy {
+///     max_attempts: 4,
+///     base_delay: Duration::from_millis(100),
+///     max_delay: Duration::from_secs(1),
+///     jitter: false,
+/// };
+/// assert_eq!(policy.delay(1), Duration::from_millis(100));
+/// assert_eq!(policy.delay(3), Duration::from_millis(400));
+/// assert_eq!(policy.delay(10), Duration::from_secs(1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns how long to wait after the given failed `attempt` (starting
+    /// at 1) before trying again.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+        if self.jitter {
+            rand::rng().random_range(delay / 2..=delay)
+        } else {
+            delay
+        }
+    }
+}
+
+/// Parses the value of a `Retry-After` header into how long to wait from
+/// `now`.
+///
+/// The header holds either a number of seconds or an HTTP date; dates
+/// already past mean retrying right away. Returns `None` if the value is
+/// neither.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::parse_retry_after;
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let now = UNIX_EPOCH + Duration::from_secs(1445412480); // 2015-10-21 07:28:00
+/// assert_eq!(parse_retry_after("120", now), Some(Duration::from_secs(120)));
+/// assert_eq!(
+///     parse_retry_after("Wed, 21 Oct 2015 07:29:30 GMT", now),
+///     Some(Duration::from_secs(90))
+/// );
+/// assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now), Some(Duration::ZERO));
+/// assert_eq!(parse_retry_after("soon", now), None);
+/// ```
+pub fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = http_date(value)?;
+    Some(at.duration_since(now).unwrap_or(Duration::ZERO))
+}
+
+/// Parses an HTTP date in its preferred format, such as
+/// `Sun, 06 Nov 1994 08:49:37 GMT`.
+fn http_date(value: &str) -> Option<SystemTime> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let [_, day, month, year, time, "GMT"] = value.split_whitespace().collect::<Vec<_>>()[..]
+    else {
+        return None;
+    };
+    let day: u64 = day.parse().ok()?;
+    let month = MONTHS.iter().position(|name| *name == month)? as u64 + 1;
+    let year: u64 = year.parse().ok()?;
+    let mut time = time.split(':').map(|part| part.parse::<u64>().ok());
+    let (Some(Some(hours)), Some(Some(minutes)), Some(Some(seconds)), None) =
+        (time.next(), time.next(), time.next(), time.next())
+    else {
+        return None;
+    };
+    if !(1..=31).contains(&day) || year < 1970 || hours > 23 || minutes > 59 || seconds > 60 {
+        return None;
+    }
+    // Days since the epoch of a date in the proleptic Gregorian calendar.
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let days = 365 * y + y / 4 - y / 100 + y / 400 + (153 * m + 2) / 5 + day - 1 - 719_468;
+    let secs = days * 86_400 + hours * 3_600 + minutes * 60 + seconds;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
@@ -0,0 +1,86 @@
+use crate::{PantherError, ResultRecord};
+use std::{collections::HashMap, time::SystemTime};
+
+/// A result kept by a [`ResultStore`], along with when it was checked.
+#[derive(Debug, Clone)]
+pub struct StoredResult {
+    pub checked_at: SystemTime,
+    pub record: ResultRecord,
+}
+
+/// Persistence for check results, keyed by checked URL.
+///
+/// Panther itself does not keep results between runs; this trait lets
+/// embedders plug in their own storage (a database, a file...) behind a
+/// common interface. [`MemoryStore`] is the implementation shipped with the
+/// crate, also handy in tests since it needs no filesystem.
+pub trait ResultStore {
+    /// Stores `record`, checked at `checked_at`.
+    fn put(&mut self, record: ResultRecord, checked_at: SystemTime) -> Result<(), PantherError>;
+
+    /// Returns the latest result stored for `url`, if any.
+    fn get(&self, url: &str) -> Result<Option<StoredResult>, PantherError>;
+
+    /// Returns every result stored for `url`, oldest first.
+    fn history(&self, url: &str) -> Result<Vec<StoredResult>, PantherError>;
+
+    /// Removes every result checked before `before`, returning how many were
+    /// removed.
+    fn prune(&mut self, before: SystemTime) -> Result<usize, PantherError>;
+}
+
+/// A [`ResultStore`] keeping everything in memory.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::{CheckStatus, MemoryStore, ResultRecord, ResultStore};
+/// use std::time::{Duration, SystemTime};
+///
+/// let record = ResultRecord::with_status("https://example.com", CheckStatus::Available);
+/// let now = SystemTime::now();
+/// let mut store = MemoryStore::default();
+/// store.put(record.clone(), now - Duration::from_secs(3600)).unwrap();
+/// store.put(record, now).unwrap();
+///
+/// assert_eq!(store.history("https://example.com").unwrap().len(), 2);
+/// assert_eq!(store.prune(now - Duration::from_secs(60)).unwrap(), 1);
+/// assert_eq!(store.get("https://example.com").unwrap().unwrap().checked_at, now);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    results: HashMap<String, Vec<StoredResult>>,
+}
+
+impl ResultStore for MemoryStore {
+    fn put(&mut self, record: ResultRecord, checked_at: SystemTime) -> Result<(), PantherError> {
+        let history = self.results.entry(record.url.clone()).or_default();
+        // Keep the history sorted even if results arrive out of order.
+        let at = history.partition_point(|stored| stored.checked_at <= checked_at);
+        history.insert(at, StoredResult { checked_at, record });
+        Ok(())
+    }
+
+    fn get(&self, url: &str) -> Result<Option<StoredResult>, PantherError> {
+        Ok(self
+            .results
+            .get(url)
+            .and_then(|history| history.last())
+            .cloned())
+    }
+
+    fn history(&self, url: &str) -> Result<Vec<StoredResult>, PantherError> {
+        Ok(self.results.get(url).cloned().unwrap_or_default())
+    }
+
+    fn prune(&mut self, before: SystemTime) -> Result<usize, PantherError> {
+        let mut removed = 0;
+        self.results.retain(|_, history| {
+            let len = history.len();
+            history.retain(|stored| stored.checked_at >= before);
+            removed += len - history.len();
+            !history.is_empty()
+        });
+        Ok(removed)
+    }
+}
@@ -0,0 +1,182 @@
+use crate::{dns::Lookup, IpVersion, PantherError};
+use reqwest::header::{HeaderMap, CONNECTION, HOST, USER_AGENT};
+use std::{
+    io,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+};
+use tokio_native_tls::{native_tls, TlsConnector};
+
+/// How long each step of a request took, measured by [`trace_timing`].
+///
+/// Every duration covers its own step only, so a slow resolver and a slow
+/// origin server show up in different fields.
+///
+/// # Fields
+///
+/// * `dns`: Resolving the host, `None` when the URL holds an IP address.
+/// * `connect`: Opening the TCP connection.
+/// * `tls`: The TLS handshake, `None` for plain `http` URLs.
+/// * `ttfb`: From sending the request to the first byte of the response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    pub dns: Option<Duration>,
+    pub connect: Duration,
+    pub tls: Option<Duration>,
+    pub ttfb: Duration,
+}
+
+/// Sends a `GET` request to `url` step by step on a connection of its own,
+/// timing each step.
+///
+/// The request goes out over HTTP/1.1 without following redirects, so only
+/// the server `url` points at is measured. It never goes through a proxy.
+///
+/// # Errors
+///
+/// * If `url` is not an absolute `http(s)` URL.
+/// * If any step fails, or if all of them take longer than `timeout`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use panther::trace_timing;
+/// use std::time::Duration;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), panther::PantherError> {
+///     let timing = trace_timing("https://example.com", Some(Duration::from_secs(10))).await?;
+///     println!("dns {:?}, tls {:?}, ttfb {:?}", timing.dns, timing.tls, timing.ttfb);
+///     Ok(())
+/// }
+/// ```
+pub async fn trace_timing(url: &str, timeout: Option<Duration>) -> Result<Timing, PantherError> {
+    let tls = native_tls::TlsConnector::new().map_err(io::Error::other)?;
+    let headers = HeaderMap::new();
+    trace_timing_with(url, timeout, &tls, None, &Lookup::System, &headers).await
+}
+/// Runs [`trace_timing`], making TLS handshakes with `tls`, connecting over
+/// `ip_version` only, if set, resolving with `dns` and sending `headers`.
+pub(crate) async fn trace_timing_with(
+    url: &str,
+    timeout: Option<Duration>,
+    tls: &native_tls::TlsConnector,
+    ip_version: Option<IpVersion>,
+    dns: &Lookup,
+    headers: &HeaderMap,
+) -> Result<Timing, PantherError> {
+    let trace = trace(url, tls, ip_version, dns, headers);
+    let Some(timeout) = timeout else {
+        return trace.await;
+    };
+    match tokio::time::timeout(timeout, trace).await {
+        Ok(timing) => timing,
+        Err(_) => Err(io::Error::new(io::ErrorKind::TimedOut, "timing trace timed out").into()),
+    }
+}
+/// Runs [`trace_timing`] without a timeout.
+async fn trace(
+    url: &str,
+    connector: &native_tls::TlsConnector,
+    ip_version: Option<IpVersion>,
+    dns: &Lookup,
+    headers: &HeaderMap,
+) -> Result<Timing, PantherError> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidInput, message.to_string());
+    let parsed = reqwest::Url::parse(url).map_err(|e| invalid(&e.to_string()))?;
+    let tls = match parsed.scheme() {
+        "https" => true,
+        "http" => false,
+        _ => return Err(invalid("only http(s) URLs can be traced").into()),
+    };
+    let (Some(host), Some(port)) = (parsed.host_str(), parsed.port_or_known_default()) else {
+        return Err(invalid("URL without a host").into());
+    };
+
+    let (addr, dns) = match parsed.domain() {
+        Some(domain) => {
+            let start = Instant::now();
+            let ip = dns
+                .lookup(domain)
+                .await?
+                .into_iter()
+                .find(|ip| ip_version.is_none_or(|version| version.matches(*ip)))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no address found"))?;
+            (SocketAddr::new(ip, port), Some(start.elapsed()))
+        }
+        // An IP address, nothing to resolve.
+        None => (parsed.socket_addrs(|| None)?[0], None),
+    };
+
+    let start = Instant::now();
+    let stream = TcpStream::connect(addr).await?;
+    let connect = start.elapsed();
+
+    let mut target = parsed.path().to_string();
+    if let Some(query) = parsed.query() {
+        target = format!("{}?{}", target, query);
+    }
+    let mut request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\n",
+        target,
+        parsed.authority()
+    )
+    .into_bytes();
+    if !headers.contains_key(USER_AGENT) {
+        request.extend_from_slice(b"User-Agent: panther\r\n");
+    }
+    for (name, value) in headers {
+        if name != HOST && name != CONNECTION {
+            request.extend_from_slice(format!("{}: ", name).as_bytes());
+            request.extend_from_slice(value.as_bytes());
+            request.extend_from_slice(b"\r\n");
+        }
+    }
+    request.extend_from_slice(b"Connection: close\r\n\r\n");
+    if !tls {
+        let ttfb = first_byte(stream, &request).await?;
+        return Ok(Timing {
+            dns,
+            connect,
+            tls: None,
+            ttfb,
+        });
+    }
+    let start = Instant::now();
+    let connector = TlsConnector::from(connector.clone());
+    let stream = connector
+        // `host_str` keeps the brackets around IPv6 addresses.
+        .connect(host.trim_start_matches('[').trim_end_matches(']'), stream)
+        .await
+        .map_err(io::Error::other)?;
+    let handshake = start.elapsed();
+    let ttfb = first_byte(stream, &request).await?;
+    Ok(Timing {
+        dns,
+        connect,
+        tls: Some(handshake),
+        ttfb,
+    })
+}
+/// Sends `request` on `stream`, returning how long the first byte of the
+/// response took to arrive.
+async fn first_byte<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    request: &[u8],
+) -> Result<Duration, PantherError> {
+    let start = Instant::now();
+    stream.write_all(request).await?;
+    let mut byte = [0; 1];
+    if stream.read(&mut byte).await? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "connection closed before any response",
+        )
+        .into());
+    }
+    Ok(start.elapsed())
+}
@@ -0,0 +1,226 @@
+use crate::PantherError;
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// A problem found in an index by [`validate_index`].
+///
+/// # Fields
+///
+/// * `line`: The line of the index where the offending extension starts.
+/// * `extension`: The name of the offending extension, if it has one.
+/// * `field`: The path to the offending field (e.g. `sources[1].baseUrl`).
+/// * `message`: What is wrong with it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub line: usize,
+    pub extension: Option<String>,
+    pub field: String,
+    pub message: String,
+}
+
+/// Validates the text of an index against the schema panther expects.
+///
+/// Every extension must have its fields with the right types, a version made
+/// of dot-separated numbers (e.g. `1.4.12`), and sources with an absolute
+/// `http(s)` base URL. Source ids must be unique across the whole index.
+/// `sources` may be left out, as [`crate::Extension`] allows, but nothing may
+/// follow the array.
+/// Every violation is reported, not just the first one, along with the line
+/// where its extension starts.
+///
+/// # Errors
+///
+/// * If `text` is not a JSON array at all, in which case there is nothing to
+///   validate.
+///
+/// # Example
+///
+/// ```rust
+/// use panther::validate_index;
+///
+/// let index = r#"[
+///   {"name": "Ext", "pkg": "p", "apk": "a.apk", "lang": "en", "code": 1,
+///    "version": "1.4", "nsfw": 0,
+///    "sources": [{"name": "S", "lang": "en", "id": "1", "baseUrl": "example.com"}]}
+/// ]"#;
+/// let violations = validate_index(index).unwrap();
+/// assert_eq!(violations.len(), 1);
+/// assert_eq!(violations[0].line, 2);
+/// assert_eq!(violations[0].field, "sources[0].baseUrl");
+/// ```
+pub fn validate_index(text: &str) -> Result<Vec<Violation>, PantherError> {
+    let version = Regex::new(r"^\d+(\.\d+)+$").expect("valid version regex");
+    let mut violations = Vec::new();
+    let mut ids: HashMap<String, usize> = HashMap::new();
+    let (elements, trailing) = elements(text)?;
+    for (offset, element) in elements {
+        let line = line_at(text, offset);
+        let mut report = |extension: Option<&str>, field: String, message: String| {
+            violations.push(Violation {
+                line,
+                extension: extension.map(str::to_string),
+                field,
+                message,
+            })
+        };
+        let Value::Object(extension) = element else {
+            report(
+                None,
+                "extension".to_string(),
+                "expected an object".to_string(),
+            );
+            continue;
+        };
+        let name = extension.get("name").and_then(Value::as_str);
+        for field in ["name", "pkg", "apk", "lang", "version"] {
+            require_string(&extension, field, field.to_string(), |field, message| {
+                report(name, field, message)
+            });
+        }
+        for field in ["code", "nsfw"] {
+            // Both are read as 32-bit integers.
+            match extension.get(field) {
+                Some(value) if value.as_i64().is_some_and(|n| i32::try_from(n).is_ok()) => {}
+                Some(value) if value.is_i64() || value.is_u64() => report(
+                    name,
+                    field.to_string(),
+                    format!("{} does not fit in a 32-bit integer", value),
+                ),
+                _ => report(name, field.to_string(), "expected an integer".to_string()),
+            }
+        }
+        if let Some(v) = extension.get("version").and_then(Value::as_str) {
+            if !version.is_match(v) {
+                report(
+                    name,
+                    "version".to_string(),
+                    format!("\"{}\" is not a version like 1.4.12", v),
+                );
+            }
+        }
+        // Extensions without sources may leave them out.
+        let sources = match extension.get("sources") {
+            None => &[][..],
+            Some(Value::Array(sources)) => &sources[..],
+            Some(_) => {
+                report(name, "sources".to_string(), "expected an array".to_string());
+                continue;
+            }
+        };
+        for (i, source) in sources.iter().enumerate() {
+            let Value::Object(source) = source else {
+                report(
+                    name,
+                    format!("sources[{}]", i),
+                    "expected a source object".to_string(),
+                );
+                continue;
+            };
+            for field in ["name", "lang", "id", "baseUrl"] {
+                require_string(
+                    source,
+                    field,
+                    format!("sources[{}].{}", i, field),
+                    |f, m| report(name, f, m),
+                );
+            }
+            if let Some(base_url) = source.get("baseUrl").and_then(Value::as_str) {
+                let valid = reqwest::Url::parse(base_url).is_ok_and(|url| {
+                    matches!(url.scheme(), "http" | "https") && url.host().is_some()
+                });
+                if !valid {
+                    report(
+                        name,
+                        format!("sources[{}].baseUrl", i),
+                        format!("\"{}\" is not an absolute http(s) URL", base_url),
+                    );
+                }
+            }
+            if let Some(id) = source.get("id").and_then(Value::as_str) {
+                if let Some(first) = ids.insert(id.to_string(), line) {
+                    report(
+                        name,
+                        format!("sources[{}].id", i),
+                        format!("duplicate id {}, first used on line {}", id, first),
+                    );
+                }
+            }
+        }
+    }
+    if let Some(offset) = trailing {
+        violations.push(Violation {
+            line: line_at(text, offset),
+            extension: None,
+            field: "index".to_string(),
+            message: "unexpected data after the array".to_string(),
+        });
+    }
+    Ok(violations)
+}
+
+/// Returns the line of `text` the byte at `offset` is on.
+fn line_at(text: &str, offset: usize) -> usize {
+    text[..offset].matches('\n').count() + 1
+}
+
+/// Reports `field` of `object` unless it is a non-empty string.
+fn require_string(
+    object: &Map<String, Value>,
+    field: &str,
+    path: String,
+    mut report: impl FnMut(String, String),
+) {
+    match object.get(field) {
+        None => report(path, "missing".to_string()),
+        Some(Value::String(value)) if value.is_empty() => report(path, "empty".to_string()),
+        Some(Value::String(_)) => {}
+        Some(_) => report(path, "expected a string".to_string()),
+    }
+}
+
+/// The elements of an index, each with the byte offset it starts at, and the
+/// offset of any data after them.
+type Elements = (Vec<(usize, Value)>, Option<usize>);
+/// Splits a JSON array into its elements, each with the byte offset it
+/// starts at, so violations can point at a line, along with the offset of
+/// anything but whitespace after the array. Syntax errors are left to
+/// parsing the whole text, so that their position is right.
+fn elements(text: &str) -> Result<Elements, PantherError> {
+    let skip = |from: usize| {
+        from + text[from..]
+            .find(|c: char| !c.is_whitespace())
+            .unwrap_or(text.len() - from)
+    };
+    let mut offset = skip(0);
+    if !text[offset..].starts_with('[') {
+        // Let serde_json describe what is there instead.
+        serde_json::from_str::<Vec<Value>>(text)?;
+    }
+    offset = skip(offset + 1);
+    let mut elements = Vec::new();
+    let trailing = |end: usize| {
+        let end = skip(end + 1);
+        (end < text.len()).then_some(end)
+    };
+    if text[offset..].starts_with(']') {
+        return Ok((elements, trailing(offset)));
+    }
+    loop {
+        let mut stream = serde_json::Deserializer::from_str(&text[offset..]).into_iter::<Value>();
+        let Some(Ok(value)) = stream.next() else {
+            serde_json::from_str::<Vec<Value>>(text)?;
+            return Ok((elements, None));
+        };
+        elements.push((offset, value));
+        offset = skip(offset + stream.byte_offset());
+        match text[offset..].chars().next() {
+            Some(',') => offset = skip(offset + 1),
+            Some(']') => return Ok((elements, trailing(offset))),
+            _ => {
+                serde_json::from_str::<Vec<Value>>(text)?;
+                return Ok((elements, None));
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use panther::{check_url, CheckOptions, CheckStatus, RetryPolicy};
+use reqwest::Method;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+/// Serves HTTP/1.1 on a local port, answering each request with the status
+/// line and headers `respond` returns for its method, path and how many
+/// requests came before it. Returns the base URL of the server.
+async fn serve<F>(respond: F) -> String
+where
+    F: Fn(&str, &str, usize) -> String + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let respond = Arc::new(respond);
+    let count = Arc::new(AtomicUsize::new(0));
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let respond = respond.clone();
+            let count = count.clone();
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buffer = [0; 1024];
+                while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                    match stream.read(&mut buffer).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => request.extend_from_slice(&buffer[..n]),
+                    }
+                }
+                let request = String::from_utf8_lossy(&request);
+                let mut line = request.split_whitespace();
+                let (method, path) = (line.next().unwrap(), line.next().unwrap());
+                let head = respond(method, path, count.fetch_add(1, Ordering::SeqCst));
+                let response =
+                    format!("{}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", head);
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    base
+}
+
+/// Options probing only the URL itself, retrying quickly.
+fn options(max_attempts: u32) -> CheckOptions {
+    CheckOptions {
+        paths: vec!["/".to_string()],
+        retry: RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(100),
+            jitter: false,
+        },
+        timeout: Some(Duration::from_secs(5)),
+        ..CheckOptions::default()
+    }
+}
+
+#[tokio::test]
+async fn retries_server_errors() {
+    let base = serve(|_, _, count| match count {
+        0 | 1 => "HTTP/1.1 503 Service Unavailable".to_string(),
+        _ => "HTTP/1.1 200 OK".to_string(),
+    })
+    .await;
+    let result = check_url(&base, &options(3)).await;
+    assert_eq!(result.status, CheckStatus::Available);
+    assert_eq!(result.attempts, 3);
+    assert!(!result.throttled);
+}
+
+#[tokio::test]
+async fn gives_up_after_max_attempts() {
+    let base = serve(|_, _, _| "HTTP/1.1 503 Service Unavailable".to_string()).await;
+    let result = check_url(&base, &options(2)).await;
+    assert_eq!(result.status, CheckStatus::NotAvailable);
+    assert_eq!(result.http_status.map(|status| status.as_u16()), Some(503));
+    assert_eq!(result.attempts, 2);
+}
+
+#[tokio::test]
+async fn falls_back_to_get_when_head_is_rejected() {
+    let base = serve(|method, _, _| match method {
+        "HEAD" => "HTTP/1.1 405 Method Not Allowed".to_string(),
+        _ => "HTTP/1.1 200 OK".to_string(),
+    })
+    .await;
+    let options = CheckOptions {
+        method: Method::HEAD,
+        ..options(3)
+    };
+    let result = check_url(&base, &options).await;
+    assert_eq!(result.status, CheckStatus::Available);
+    assert_eq!(result.method, Some(Method::GET));
+}
+
+#[tokio::test]
+async fn retries_a_429_once_and_flags_it() {
+    let base = serve(|_, _, count| match count {
+        0 => "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 0".to_string(),
+        _ => "HTTP/1.1 200 OK".to_string(),
+    })
+    .await;
+    // The retry of a 429 is not taken from the transient retries.
+    let result = check_url(&base, &options(1)).await;
+    assert_eq!(result.status, CheckStatus::Available);
+    assert_eq!(result.attempts, 2);
+    assert!(result.throttled);
+}
+
+#[tokio::test]
+async fn records_redirects() {
+    let base = serve(|_, path, _| match path {
+        "/" => "HTTP/1.1 302 Found\r\nLocation: /moved".to_string(),
+        _ => "HTTP/1.1 200 OK".to_string(),
+    })
+    .await;
+    let result = check_url(&base, &options(1)).await;
+    assert_eq!(result.status, CheckStatus::Available);
+    assert_eq!(
+        result.redirects,
+        vec![format!("{}/", base), format!("{}/moved", base)]
+    );
+    assert_eq!(result.final_url(), Some(format!("{}/moved", base).as_str()));
+    assert_eq!(result.moved_to(), None);
+}